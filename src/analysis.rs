@@ -0,0 +1,675 @@
+//! 对一手牌的所有解做挑选与分析。
+
+use crate::answer::check_answer;
+use crate::card::{validate_cards, HandError, HAND_SIZE};
+use crate::expr::{canonical, display_minimal, parse_expr, Expr};
+use crate::rational::exact_intermediates;
+use crate::solution::{solve_24_canonical, solve_24_canonical_trees, solve_24_full, Solution};
+use crate::{is_solvable, solve_no_division, solve_subset, solve_with_ops, OPERATORS};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+fn solution_trees(cards: &[i32]) -> Vec<Expr> {
+    solve_24_full(cards).into_iter().map(|s| s.expr).collect()
+}
+
+fn division_count(expr: &Expr) -> usize {
+    expr.operators().iter().filter(|&&op| op == '/').count()
+}
+
+// Ordering key shared by `best_solution` and friends: smaller is simpler.
+fn complexity(expr: &Expr) -> (usize, usize) {
+    (expr.depth(), division_count(expr))
+}
+
+/// 返回一手牌"最简单"的解，无解时返回 `None`。
+///
+/// 依次比较：树的深度越浅越好、除法越少越好，最后按字符串字典序取最小者，
+/// 保证结果稳定。
+pub fn best_solution(cards: &[i32]) -> Option<String> {
+    best_of(&solve_24_full(cards)).map(|s| s.text.clone())
+}
+
+/// 按 `best_solution` 的标准从已经求出的解中挑出最简单的一个，`solutions` 为空时返回 `None`。
+///
+/// 适合已经按其他配置（如 `solve_with_config_full`）求过解的调用方，不必再求解一次。
+pub fn best_of(solutions: &[Solution]) -> Option<&Solution> {
+    solutions
+        .iter()
+        .min_by(|a, b| (complexity(&a.expr), &a.text).cmp(&(complexity(&b.expr), &b.text)))
+}
+
+/// 精简的答案表：每个交换律等价类（见 `solve_24_canonical`）只保留一个代表解。
+///
+/// 代表解按 `best_solution` 的标准在类内挑选：深度最浅、除法最少，最后取字典序最小者，
+/// 返回的是该解原本的写法而不是规范形式。结果按字典序排列，个数与
+/// `solve_24_canonical` 相同。
+pub fn minimal_basis(cards: &[i32]) -> Vec<String> {
+    let mut classes: HashMap<String, ((usize, usize), String)> = HashMap::new();
+    for expr in solution_trees(cards) {
+        let candidate = (complexity(&expr), expr.to_string());
+        classes
+            .entry(canonical(&expr).to_string())
+            .and_modify(|best| {
+                if candidate < *best {
+                    *best = candidate.clone();
+                }
+            })
+            .or_insert(candidate);
+    }
+    let mut basis: Vec<String> = classes.into_values().map(|(_, text)| text).collect();
+    basis.sort();
+    basis
+}
+
+/// "表达式高尔夫"：返回写出来最短的解，无解时返回 `None`。
+///
+/// 长度按 `display_minimal`（省略多余括号）的渲染结果计算，返回的也是这种写法；
+/// 长度相同时按字典序取最小者。
+pub fn shortest_solution(cards: &[i32]) -> Option<String> {
+    solution_trees(cards)
+        .iter()
+        .map(display_minimal)
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+}
+
+/// 判断玩家给出的解是否已经是"最优答案"，即不存在更简单的解。
+///
+/// 先用 `check_answer` 确认它确实是这手牌的正确答案（否则返回 `false`），
+/// 再用与 `best_solution` 相同的标准（深度、除法个数）比较：
+/// 只要不比 `best_solution` 复杂就算最优，因此并列最简的解都返回 `true`。
+pub fn is_minimal_solution(cards: &[i32], solution: &str) -> bool {
+    if check_answer(cards, solution) != Ok(true) {
+        return false;
+    }
+    let Ok(expr) = parse_expr(solution) else {
+        return false;
+    };
+    best_of(&solve_24_full(cards)).is_some_and(|best| complexity(&expr) <= complexity(&best.expr))
+}
+
+/// 返回一手牌"最绕"的解，是 `best_solution` 的反面，无解时返回 `None`。
+///
+/// 依次比较：树的深度越深越好、除法越多越好；分数相同时仍按字符串字典序
+/// 取最小者，保证结果稳定。
+pub fn hardest_solution(cards: &[i32]) -> Option<String> {
+    solution_trees(cards)
+        .into_iter()
+        .map(|expr| (std::cmp::Reverse(complexity(&expr)), expr.to_string()))
+        .min()
+        .map(|(_, text)| text)
+}
+
+/// 返回用到的运算符种类最少的解，无解时返回 `None`。
+///
+/// 例如 `[2, 2, 2, 3]` 可以只用乘法解出。种类相同时取深度较浅的，
+/// 最后按字符串字典序取最小者，保证结果稳定。
+pub fn fewest_distinct_ops_solution(cards: &[i32]) -> Option<String> {
+    solution_trees(cards)
+        .into_iter()
+        .map(|expr| {
+            let kinds = expr.operators().into_iter().collect::<HashSet<_>>().len();
+            ((kinds, expr.depth()), expr.to_string())
+        })
+        .min()
+        .map(|(_, text)| text)
+}
+
+/// 统计 `+ - * /` 四种运算符中，有多少种至少出现在这手牌的某个解里。
+///
+/// 得 4 分的牌在解集中用到了全部四种运算；无解时为 0。
+pub fn operator_coverage(cards: &[i32]) -> usize {
+    let used: HashSet<char> = solution_trees(cards)
+        .iter()
+        .flat_map(|expr| expr.operators())
+        .collect();
+    OPERATORS.iter().filter(|op| used.contains(op)).count()
+}
+
+/// 判断一手牌是否"公平"：不用除法也能凑出 24。
+///
+/// 用于给初学者筛选题目，等价于 `solve_no_division` 的结果非空。
+pub fn is_fair(cards: &[i32]) -> bool {
+    !solve_no_division(cards).is_empty()
+}
+
+/// 返回在运算符集合 `ops_b` 下成立、但在 `ops_a` 下找不到的解，按字典序排列。
+///
+/// 例如比较 `['+', '-', '*']` 与 `OPERATORS`，即可看到一手牌允许除法后新增了哪些解。
+/// 这里按求解器输出的表达式字符串做集合差。
+pub fn solution_diff(cards: &[i32], ops_a: &[char], ops_b: &[char]) -> Vec<String> {
+    let base: HashSet<String> = solve_with_ops(cards, ops_a).into_iter().collect();
+    let mut gained: Vec<String> = solve_with_ops(cards, ops_b)
+        .into_iter()
+        .filter(|s| !base.contains(s))
+        .collect();
+    gained.sort();
+    gained
+}
+
+/// 如果去掉某一张牌后剩下的牌仍能凑出 24，返回这张"多余"牌的下标。
+///
+/// 例如 `[2, 3, 4, 13]` 中 `2 * 3 * 4` 已经等于 24，13 就是多余的。
+/// 有多张多余牌时返回下标最小的一张；没有则返回 `None`。
+pub fn has_redundant_card(cards: &[i32]) -> Option<usize> {
+    (0..cards.len()).find(|&i| {
+        let mut rest = cards.to_vec();
+        rest.remove(i);
+        !solve_subset(&rest).is_empty()
+    })
+}
+
+/// `min_cards_to_target` 最多检查的牌数。
+pub const MAX_TARGET_CARDS: usize = 6;
+
+/// 从这手牌中最少取几张就能凑出 24（每张取出的牌恰好用一次），完全无解时返回 `None`。
+///
+/// 按张数从小到大检查所有子集，对每个子集调用 `solve_subset`；
+/// 例如 `[6, 4, 1, 1]` 只需 `6 * 4` 两张，返回 2。
+/// 子集数与每个子集的搜索量都随张数指数增长，因此超过 `MAX_TARGET_CARDS`
+/// 张牌时不做搜索，直接返回 `None`。
+pub fn min_cards_to_target(cards: &[i32]) -> Option<usize> {
+    let n = cards.len();
+    if n > MAX_TARGET_CARDS {
+        return None;
+    }
+    (1..=n).find(|&size| {
+        (0u32..1 << n)
+            .filter(|mask| mask.count_ones() as usize == size)
+            .any(|mask| {
+                let subset: Vec<i32> = (0..n)
+                    .filter(|i| mask & (1 << i) != 0)
+                    .map(|i| cards[i])
+                    .collect();
+                !solve_subset(&subset).is_empty()
+            })
+    })
+}
+
+/// 按出现顺序列出解字符串中的二元运算符，不需要解析成表达式树。
+///
+/// 除了 ASCII 的 `+ - * /`，也识别 Unicode 写法 `×`、`÷`、`−`（U+2212），
+/// 并统一映射回 ASCII 运算符。紧跟在开头、左括号或另一个运算符之后的减号
+/// 是一元负号，不计入结果。
+pub fn operators_in(solution: &str) -> Vec<char> {
+    let mut ops = Vec::new();
+    // Whether the previous significant character can end an operand.
+    let mut after_operand = false;
+    for c in solution.chars().filter(|c| !c.is_whitespace()) {
+        let op = match c {
+            '+' => Some('+'),
+            '-' | '−' => Some('-'),
+            '*' | '×' => Some('*'),
+            '/' | '÷' => Some('/'),
+            _ => None,
+        };
+        match op {
+            Some(op) if after_operand => {
+                ops.push(op);
+                after_operand = false;
+            }
+            Some(_) => {}
+            None => after_operand = c != '(',
+        }
+    }
+    ops
+}
+
+/// 统计一手牌所有解的中间结果中，出现过多少个不同的非整数值。
+///
+/// 中间值用 `rational` 模块精确计算，因此 `8 / 3` 这样的分数不会受浮点误差影响。
+/// 只能靠大量分数周转才能解出的牌，这个数会比较大。
+pub fn distinct_fractions(cards: &[i32]) -> usize {
+    solution_trees(cards)
+        .iter()
+        .filter_map(exact_intermediates)
+        .flatten()
+        .filter(|value| !value.is_integer())
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// 可以作为 `HashMap` 键的 `f64`：按位比较与哈希，排序用 `f64::total_cmp`。
+///
+/// `0.0` 与 `-0.0` 视为同一个键；本模块产生的值不会是 NaN。
+#[derive(Debug, Clone, Copy)]
+pub struct OrderedFloat(pub f64);
+
+impl OrderedFloat {
+    // -0.0 and 0.0 compare equal as f64, so they must share a key.
+    fn bits(self) -> u64 {
+        if self.0 == 0.0 {
+            0
+        } else {
+            self.0.to_bits()
+        }
+    }
+}
+
+impl PartialEq for OrderedFloat {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits() == other.bits()
+    }
+}
+
+impl Eq for OrderedFloat {}
+
+impl Hash for OrderedFloat {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits().hash(state);
+    }
+}
+
+impl PartialOrd for OrderedFloat {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedFloat {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        if self == other {
+            std::cmp::Ordering::Equal
+        } else {
+            self.0.total_cmp(&other.0)
+        }
+    }
+}
+
+/// 统计一手牌所有解中，每个二元运算节点的值出现了多少次。
+///
+/// 与 `distinct_fractions` 一样用 `rational` 模块精确计算中间值后再转成 `f64`，
+/// 同一个分数总是落在同一个键上。每个解的最终结果 24 也计入；叶子（牌本身）不计。
+/// 解按 `solve_24` 的字符串去重，无解时返回空表。
+pub fn intermediate_value_histogram(cards: &[i32]) -> HashMap<OrderedFloat, usize> {
+    let mut histogram = HashMap::new();
+    for value in solution_trees(cards)
+        .iter()
+        .filter_map(exact_intermediates)
+        .flatten()
+    {
+        *histogram.entry(OrderedFloat(value.to_f64())).or_insert(0) += 1;
+    }
+    histogram
+}
+
+/// 一手牌所有解的表达式树平均深度，无解时返回 `None`。
+///
+/// 按 `solve_24_canonical` 去重后的解计算，交换律等价的写法只算一次。
+/// 四张牌的解深度为 2 或 3，只能用嵌套形态解出的牌平均深度为 3，通常也更难。
+pub fn average_solution_depth(cards: &[i32]) -> Option<f64> {
+    let depths: Vec<usize> = solve_24_canonical_trees(cards)
+        .iter()
+        .map(Expr::depth)
+        .collect();
+    if depths.is_empty() {
+        return None;
+    }
+    Some(depths.iter().sum::<usize>() as f64 / depths.len() as f64)
+}
+
+/// 一手有解的牌的难度指标，由 `difficulty` 计算。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DifficultyMetrics {
+    /// 按 `solve_24_canonical` 去重后的解数。
+    pub solutions: usize,
+    /// 是否必须用除法（即不"公平"，见 `is_fair`）。
+    pub requires_division: bool,
+    /// 最浅的解的树深度（2 或 3）。
+    pub min_depth: usize,
+}
+
+impl DifficultyMetrics {
+    /// 综合难度的排序键，越大越难。
+    ///
+    /// 依次比较：解越少越难；必须用除法的更难；连最浅的解都要嵌套（深度更大）的更难。
+    pub fn hardness_key(&self) -> (std::cmp::Reverse<usize>, bool, usize) {
+        (
+            std::cmp::Reverse(self.solutions),
+            self.requires_division,
+            self.min_depth,
+        )
+    }
+}
+
+/// 计算一手牌的难度指标，无解时返回 `None`。
+pub fn difficulty(cards: &[i32]) -> Option<DifficultyMetrics> {
+    let trees = solve_24_canonical_trees(cards);
+    let min_depth = trees.iter().map(Expr::depth).min()?;
+    Some(DifficultyMetrics {
+        solutions: trees.len(),
+        requires_division: !is_fair(cards),
+        min_depth,
+    })
+}
+
+/// 判断两手牌的解集是否相同（按 `solve_24_canonical` 的规范形式比较）。
+///
+/// 这是解集的相等而不是牌的相等：解中写着具体的点数，所以点数不同的两手有解的牌
+/// 不会相等，同一组点数换个顺序则一定相等；两手无解的牌解集都为空，也算相等。
+pub fn same_solutions(a: &[i32], b: &[i32]) -> bool {
+    solve_24_canonical(a) == solve_24_canonical(b)
+}
+
+/// 一手牌解集的"特征"，用于把风格相近的牌归为一类。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HandSignature {
+    /// 所有解的根运算符（最后一步运算）的集合。
+    pub root_ops: BTreeSet<char>,
+    /// 是否必须用除法（见 `is_fair`）。
+    pub needs_division: bool,
+}
+
+/// 计算一手牌的 `HandSignature`；无解的牌 `root_ops` 为空。
+pub fn hand_signature(cards: &[i32]) -> HandSignature {
+    let root_ops = solution_trees(cards)
+        .iter()
+        .filter_map(|expr| match expr {
+            Expr::Bin(op, ..) => Some(*op),
+            _ => None,
+        })
+        .collect();
+    HandSignature {
+        root_ops,
+        needs_division: !is_fair(cards),
+    }
+}
+
+/// 两手牌逐位置的点数差的绝对值之和。
+pub fn hand_distance(a: &[i32], b: &[i32]) -> i32 {
+    a.iter().zip(b).map(|(x, y)| (x - y).abs()).sum()
+}
+
+/// 找出与 `cards` 距离（`hand_distance`）最近的有解牌型，可以同时改动多张牌。
+///
+/// 按距离从小到大（距离相同时按牌面字典序）检查 1~13 的所有 4 张牌组合，
+/// 返回第一手有解的牌；`cards` 本身有解时原样返回。`cards` 不是 4 张时返回
+/// `HandError::WrongCount`；点数可以超出 1~13，此时返回与它最近的合法牌型。
+pub fn nearest_solvable(cards: &[i32]) -> Result<Vec<i32>, HandError> {
+    if cards.len() != HAND_SIZE {
+        return Err(HandError::WrongCount {
+            expected: HAND_SIZE,
+            got: cards.len(),
+        });
+    }
+    let mut candidates: Vec<Vec<i32>> = Vec::new();
+    for a in 1..=13 {
+        for b in 1..=13 {
+            for c in 1..=13 {
+                for d in 1..=13 {
+                    candidates.push(vec![a, b, c, d]);
+                }
+            }
+        }
+    }
+    candidates.sort_by_key(|hand| (hand_distance(cards, hand), hand.clone()));
+    Ok(candidates
+        .into_iter()
+        .find(|hand| is_solvable(hand))
+        .expect("some 4-card hand in 1..=13 is solvable"))
+}
+
+/// "选出正确的第四张牌"：列出补上哪些点数（1~13）后，三张牌 `three` 凑成的四张牌有解。
+///
+/// 对 13 个候选各调用一次 `is_solvable`，结果从小到大排列。
+/// `three` 不是 3 张 1~13 的牌时返回对应的 `HandError`。
+pub fn completing_cards(three: &[i32]) -> Result<Vec<i32>, HandError> {
+    validate_cards(three, HAND_SIZE - 1)?;
+    Ok((1..=13)
+        .filter(|&card| {
+            let mut hand = three.to_vec();
+            hand.push(card);
+            is_solvable(&hand)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_best_solution_prefers_shallow() {
+        let best = best_solution(&[6, 2, 3, 4]).unwrap();
+        assert_eq!(parse_expr(&best).unwrap().depth(), 2);
+        assert_eq!(best_solution(&[1, 1, 1, 1]), None);
+        let solutions = solve_24_full(&[6, 2, 3, 4]);
+        assert_eq!(best_of(&solutions).map(|s| &s.text), Some(&best));
+        assert_eq!(best_of(&[]), None);
+    }
+
+    #[test]
+    fn test_hardest_solution_prefers_nested() {
+        // [6, 2, 3, 4] has both `(6 * 2) + (3 * 4)` and deeper chained solutions.
+        let hardest = hardest_solution(&[6, 2, 3, 4]).unwrap();
+        let expr = parse_expr(&hardest).unwrap();
+        assert_eq!(expr.depth(), 3);
+        assert!(hardest.contains('/'));
+        assert_eq!(hardest_solution(&[1, 1, 1, 1]), None);
+    }
+
+    #[test]
+    fn test_is_fair() {
+        assert!(is_fair(&[6, 6, 6, 6]));
+        // 8 / (3 - 8 / 3) is the only way to solve this hand.
+        assert!(!is_fair(&[3, 3, 8, 8]));
+    }
+
+    #[test]
+    fn test_solution_diff_division() {
+        let gained = solution_diff(&[3, 3, 8, 8], &['+', '-', '*'], &crate::OPERATORS);
+        assert_eq!(gained, vec!["8 / (3 - (8 / 3))".to_string()]);
+        assert!(gained.iter().all(|s| s.contains('/')));
+
+        // Nothing is gained when both sides use the same operators.
+        assert!(solution_diff(&[6, 2, 3, 4], &['+', '*'], &['+', '*']).is_empty());
+    }
+
+    #[test]
+    fn test_has_redundant_card() {
+        assert_eq!(has_redundant_card(&[2, 3, 4, 13]), Some(3));
+        // 6 * 4 * 1 works without the 2.
+        assert_eq!(has_redundant_card(&[6, 4, 1, 2]), Some(3));
+        assert_eq!(has_redundant_card(&[3, 3, 8, 8]), None);
+    }
+
+    #[test]
+    fn test_operators_in() {
+        assert_eq!(operators_in("(6 * 2) + (3 * 4)"), vec!['*', '+', '*']);
+        assert_eq!(operators_in("8 / (3 - (8 / 3))"), vec!['/', '-', '/']);
+        assert_eq!(
+            operators_in("(6 × 2) + (3 ÷ 4) − 1"),
+            vec!['*', '+', '/', '-']
+        );
+        // Unary minus is not a binary operator.
+        assert_eq!(operators_in("-3 + (-4 * 2)"), vec!['+', '*']);
+    }
+
+    #[test]
+    fn test_intermediate_value_histogram() {
+        let cards = [2, 3, 4, 6];
+        let histogram = intermediate_value_histogram(&cards);
+        // Every solution ends in 24; some, like (4 * 6) * (3 - 2), also pass through it.
+        assert!(histogram[&OrderedFloat(24.0)] >= crate::count_solutions(&cards));
+        for value in [6.0, 12.0] {
+            assert!(histogram[&OrderedFloat(value)] > 0, "{}", value);
+        }
+        assert!(intermediate_value_histogram(&[1, 1, 1, 1]).is_empty());
+        assert_eq!(OrderedFloat(0.0), OrderedFloat(-0.0));
+    }
+
+    #[test]
+    fn test_minimal_basis() {
+        for cards in [[6, 2, 3, 4], [3, 3, 8, 8], [1, 1, 1, 1]] {
+            let basis = minimal_basis(&cards);
+            assert_eq!(basis.len(), solve_24_canonical(&cards).len(), "{:?}", cards);
+            let classes: HashSet<String> = basis
+                .iter()
+                .map(|s| canonical(&parse_expr(s).unwrap()).to_string())
+                .collect();
+            assert_eq!(classes.len(), basis.len());
+        }
+        // (6 * 4) * (3 - 2) and its commuted forms share a class; the smallest string is kept.
+        let basis = minimal_basis(&[6, 2, 3, 4]);
+        assert!(basis.contains(&"(3 - 2) * (4 * 6)".to_string()));
+        assert!(!basis.contains(&"(6 * 4) * (3 - 2)".to_string()));
+    }
+
+    #[test]
+    fn test_distinct_fractions() {
+        // 8 / (3 - 8 / 3) passes through 8/3 and 1/3.
+        assert_eq!(distinct_fractions(&[3, 3, 8, 8]), 2);
+        assert_eq!(distinct_fractions(&[1, 1, 1, 1]), 0);
+        assert_eq!(distinct_fractions(&[2, 2, 2, 3]), 0);
+    }
+
+    #[test]
+    fn test_is_minimal_solution() {
+        let cards = [6, 2, 3, 4];
+        let best = best_solution(&cards).unwrap();
+        assert!(is_minimal_solution(&cards, &best));
+        assert!(is_minimal_solution(&cards, "(3 * 4) + (6 * 2)"));
+
+        let nested = hardest_solution(&cards).unwrap();
+        assert!(!is_minimal_solution(&cards, &nested));
+        // Wrong answers and garbage are never minimal.
+        assert!(!is_minimal_solution(&cards, "(6 + 2) + (3 + 4)"));
+        assert!(!is_minimal_solution(&cards, "6 * ("));
+    }
+
+    #[test]
+    fn test_nearest_solvable() {
+        let cards = [1, 1, 1, 1];
+        let nearest = nearest_solvable(&cards).unwrap();
+        assert!(is_solvable(&nearest));
+        let distance = hand_distance(&cards, &nearest);
+        assert!(distance > 0);
+
+        // No hand strictly closer than `nearest` is solvable.
+        for a in 1..=13 {
+            for b in 1..=13 {
+                for c in 1..=13 {
+                    for d in 1..=13 {
+                        let hand = [a, b, c, d];
+                        if hand_distance(&cards, &hand) < distance {
+                            assert!(!is_solvable(&hand), "{:?}", hand);
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(nearest_solvable(&[3, 3, 8, 8]), Ok(vec![3, 3, 8, 8]));
+        assert_eq!(
+            nearest_solvable(&[3, 3, 8]),
+            Err(HandError::WrongCount {
+                expected: 4,
+                got: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_average_solution_depth() {
+        // 8 / (3 - 8 / 3) is nested all the way down.
+        assert_eq!(average_solution_depth(&[3, 3, 8, 8]), Some(3.0));
+        let shallow = average_solution_depth(&[6, 2, 3, 4]).unwrap();
+        assert!((2.0..3.0).contains(&shallow), "{}", shallow);
+        assert_eq!(average_solution_depth(&[1, 1, 1, 1]), None);
+    }
+
+    #[test]
+    fn test_fewest_distinct_ops_solution() {
+        let best = fewest_distinct_ops_solution(&[2, 2, 2, 3]).unwrap();
+        assert_eq!(best, "(2 * 2) * (2 * 3)");
+        // 8 / (3 - 8 / 3) needs both division and subtraction.
+        assert_eq!(
+            fewest_distinct_ops_solution(&[3, 3, 8, 8]).as_deref(),
+            Some("8 / (3 - (8 / 3))")
+        );
+        assert_eq!(fewest_distinct_ops_solution(&[1, 1, 1, 1]), None);
+    }
+
+    #[test]
+    fn test_difficulty() {
+        let hard = difficulty(&[3, 3, 8, 8]).unwrap();
+        assert_eq!(
+            hard,
+            DifficultyMetrics {
+                solutions: 1,
+                requires_division: true,
+                min_depth: 3,
+            }
+        );
+        let easy = difficulty(&[6, 2, 3, 4]).unwrap();
+        assert!(easy.hardness_key() < hard.hardness_key());
+        assert_eq!(difficulty(&[1, 1, 1, 1]), None);
+    }
+
+    #[test]
+    fn test_operator_coverage() {
+        assert_eq!(operator_coverage(&[6, 2, 3, 4]), 4);
+        // (1 + 1 + 1) * 8 is the only way, in any order.
+        assert_eq!(operator_coverage(&[1, 1, 1, 8]), 2);
+        assert_eq!(operator_coverage(&[1, 1, 1, 1]), 0);
+    }
+
+    #[test]
+    fn test_same_solutions() {
+        assert!(same_solutions(&[3, 3, 8, 8], &[8, 3, 8, 3]));
+        assert!(!same_solutions(&[3, 3, 8, 8], &[6, 2, 3, 4]));
+        // Both solution sets are empty.
+        assert!(same_solutions(&[1, 1, 1, 1], &[1, 1, 1, 2]));
+    }
+
+    #[test]
+    fn test_shortest_solution() {
+        let shortest = shortest_solution(&[6, 2, 3, 4]).unwrap();
+        assert_eq!(shortest, "2 * 6 + 3 * 4");
+        assert!(shortest.len() < "(6 * 2) + (3 * 4)".len());
+        assert_eq!(shortest_solution(&[1, 1, 1, 1]), None);
+    }
+
+    #[test]
+    fn test_min_cards_to_target() {
+        assert_eq!(min_cards_to_target(&[6, 4, 1, 1]), Some(2));
+        assert_eq!(min_cards_to_target(&[2, 3, 4, 13]), Some(3));
+        // Only (1 + 1 + 1) * 8 works.
+        assert_eq!(min_cards_to_target(&[1, 1, 1, 8]), Some(4));
+        assert_eq!(min_cards_to_target(&[1, 1, 1, 1]), None);
+        // Too many cards to search, even though 6 * 4 alone would do.
+        let long = [6, 4, 1, 1, 1, 1, 1];
+        assert!(long.len() > MAX_TARGET_CARDS);
+        assert_eq!(min_cards_to_target(&long), None);
+    }
+
+    #[test]
+    fn test_hand_signature() {
+        // Both finish with a division: 8 / (3 - 8 / 3) and (7 * 7 - 1) / 2.
+        let a = hand_signature(&[3, 3, 8, 8]);
+        let b = hand_signature(&[1, 2, 7, 7]);
+        assert_eq!(a, b);
+        assert_eq!(a.root_ops, BTreeSet::from(['/']));
+        assert!(a.needs_division);
+        assert_ne!(hand_signature(&[6, 2, 3, 4]), a);
+    }
+
+    #[test]
+    fn test_completing_cards() {
+        let three = [1, 1, 1];
+        let expected: Vec<i32> = (1..=13)
+            .filter(|&c| !crate::solve_24(&[1, 1, 1, c]).is_empty())
+            .collect();
+        assert_eq!(completing_cards(&three), Ok(expected));
+        // ((1 + 1) + 1) * 8 is the smallest completion.
+        assert_eq!(completing_cards(&three).unwrap().first(), Some(&8));
+        assert!(completing_cards(&[3, 3, 8]).unwrap().contains(&8));
+        assert_eq!(
+            completing_cards(&[3, 3, 8, 8]),
+            Err(HandError::WrongCount {
+                expected: 3,
+                got: 4
+            })
+        );
+        assert_eq!(completing_cards(&[3, 3, 0]), Err(HandError::OutOfRange(0)));
+    }
+}
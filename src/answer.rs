@@ -0,0 +1,208 @@
+//! 检查玩家给出的答案。
+
+use crate::expr::{eval, parse_expr, parse_expr_with, Associativity, Expr, ParseError};
+use crate::{EPSILON, TARGET};
+use std::fmt;
+
+/// 按出现顺序收集表达式树的所有数字叶子。
+pub(crate) fn leaves(expr: &Expr) -> Vec<f64> {
+    match expr {
+        Expr::Num(v) => vec![*v],
+        Expr::Neg(inner) | Expr::Abs(inner) => leaves(inner),
+        Expr::Bin(_, lhs, rhs) => {
+            let mut values = leaves(lhs);
+            values.extend(leaves(rhs));
+            values
+        }
+    }
+}
+
+/// 判断表达式用到的数字是否恰好是这手牌（与顺序无关，每张牌用且只用一次）。
+pub(crate) fn uses_cards(expr: &Expr, cards: &[i32]) -> bool {
+    let mut used = leaves(expr);
+    let mut expected: Vec<f64> = cards.iter().map(|&c| c as f64).collect();
+    used.sort_by(f64::total_cmp);
+    expected.sort_by(f64::total_cmp);
+    used == expected
+}
+
+/// 检查玩家输入的算式是否是这手牌的一个正确答案。
+///
+/// 算式无法解析时返回 `Err`；能解析时，只有恰好用完这四张牌、
+/// 且结果与 `TARGET` 在 `EPSILON` 范围内相等才返回 `Ok(true)`。
+pub fn check_answer(cards: &[i32], answer: &str) -> Result<bool, ParseError> {
+    check_answer_with(cards, answer, Associativity::Left)
+}
+
+/// 与 `check_answer` 相同，但按 `assoc` 理解没有括号的同级连写，
+/// 用于把 `a - b - c` 解释为 `a - (b - c)` 的变体玩法。
+pub fn check_answer_with(
+    cards: &[i32],
+    answer: &str,
+    assoc: Associativity,
+) -> Result<bool, ParseError> {
+    let expr = parse_expr_with(answer, assoc)?;
+    Ok(uses_cards(&expr, cards) && eval(&expr).is_some_and(|v| (v - TARGET).abs() < EPSILON))
+}
+
+/// `score_answer` 无法给出分数的原因。
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoreError {
+    /// 算式本身无法解析。
+    Parse(ParseError),
+    /// 算式用到的数字不是恰好这手牌。
+    WrongCards,
+}
+
+impl fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoreError::Parse(e) => write!(f, "{}", e),
+            ScoreError::WrongCards => write!(f, "expression must use each card exactly once"),
+        }
+    }
+}
+
+impl std::error::Error for ScoreError {}
+
+impl From<ParseError> for ScoreError {
+    fn from(e: ParseError) -> Self {
+        ScoreError::Parse(e)
+    }
+}
+
+/// 宽松的评分：按玩家答案与 `target` 的接近程度给出 0~1 之间的分数。
+///
+/// 分数为 `1 / (1 + |结果 - target|)`：恰好等于 `target`（误差在 `EPSILON` 以内）
+/// 得 1 分，差 1 得 0.5 分，差得越多越接近 0；计算中出现除以 0 时得 0 分。
+/// 算式无法解析时返回 `ScoreError::Parse`，用到的数字不是恰好这手牌时返回
+/// `ScoreError::WrongCards`。
+pub fn score_answer(cards: &[i32], answer: &str, target: f64) -> Result<f64, ScoreError> {
+    let expr = parse_expr(answer)?;
+    if !uses_cards(&expr, cards) {
+        return Err(ScoreError::WrongCards);
+    }
+    Ok(match eval(&expr) {
+        Some(v) if (v - target).abs() < EPSILON => 1.0,
+        Some(v) => 1.0 / (1.0 + (v - target).abs()),
+        None => 0.0,
+    })
+}
+
+/// `diagnose_answer` 对玩家答案的诊断结果。
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnosis {
+    /// 用对了牌，结果也等于 `TARGET`。
+    CorrectValue,
+    /// 用对了牌，但结果是别的数。
+    WrongValue(f64),
+    /// 用对了牌，但计算中出现除以 0，结果没有定义。
+    Undefined,
+    /// 用到的数字与这手牌不一致；两边都按从小到大排序。
+    WrongCards { expected: Vec<i32>, got: Vec<i32> },
+    /// 算式本身无法解析。
+    ParseError(ParseError),
+}
+
+/// 诊断玩家的答案错在哪里，供教学模式给出具体的提示。
+///
+/// 先检查能否解析，再检查用到的数字是否恰好是这手牌，最后才看结果；
+/// 因此牌用错时即使结果碰巧是 24 也报告 `WrongCards`。
+pub fn diagnose_answer(cards: &[i32], answer: &str) -> Diagnosis {
+    let expr = match parse_expr(answer) {
+        Ok(expr) => expr,
+        Err(e) => return Diagnosis::ParseError(e),
+    };
+    if !uses_cards(&expr, cards) {
+        let mut expected = cards.to_vec();
+        expected.sort();
+        // The parser only produces integer leaves.
+        let mut got: Vec<i32> = leaves(&expr).into_iter().map(|v| v as i32).collect();
+        got.sort();
+        return Diagnosis::WrongCards { expected, got };
+    }
+    match eval(&expr) {
+        Some(v) if (v - TARGET).abs() < EPSILON => Diagnosis::CorrectValue,
+        Some(v) => Diagnosis::WrongValue(v),
+        None => Diagnosis::Undefined,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_answer() {
+        assert_eq!(check_answer(&[3, 3, 8, 8], "8 / (3 - 8 / 3)"), Ok(true));
+        // Right value, wrong cards.
+        assert_eq!(check_answer(&[3, 3, 8, 8], "6 * 4"), Ok(false));
+        // Right cards, wrong value.
+        assert_eq!(check_answer(&[3, 3, 8, 8], "3 + 3 + 8 + 8"), Ok(false));
+        assert!(check_answer(&[3, 3, 8, 8], "8 / (3 -").is_err());
+        // Absolute value is not part of the standard game.
+        let err = check_answer(&[3, 3, 8, 8], "8 / |(8 / 3) - 3|").unwrap_err();
+        assert_eq!(err.message, "unexpected character '|'");
+    }
+
+    #[test]
+    fn test_check_answer_right_assoc() {
+        // Left-assoc reads (24 - 1) - 1 = 22, right-assoc 24 - (1 - 1) = 24.
+        let cards = [12, 2, 1, 1];
+        assert_eq!(check_answer(&cards, "12 * 2 - 1 - 1"), Ok(false));
+        assert_eq!(
+            check_answer_with(&cards, "12 * 2 - 1 - 1", Associativity::Right),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_score_answer() {
+        let cards = [3, 3, 8, 8];
+        assert_eq!(score_answer(&cards, "8 / (3 - 8 / 3)", 24.0), Ok(1.0));
+        // 3 * 8 - 8 + 3 = 19, off by 5; (8 - 3) * 3 + 8 = 23 is closer.
+        let far = score_answer(&cards, "3 * 8 - 8 + 3", 24.0).unwrap();
+        let near = score_answer(&cards, "(8 - 3) * 3 + 8", 24.0).unwrap();
+        assert_eq!(near, 0.5);
+        assert!(far < near && far > 0.0);
+        // 3 + 3 + 8 + 8 = 22 scores full marks when the target is 22.
+        assert_eq!(score_answer(&cards, "3 + 3 + 8 + 8", 22.0), Ok(1.0));
+        assert_eq!(score_answer(&cards, "8 / (3 - 3) + 8", 24.0), Ok(0.0));
+        assert_eq!(
+            score_answer(&cards, "6 * 4", 24.0),
+            Err(ScoreError::WrongCards)
+        );
+        assert!(matches!(
+            score_answer(&cards, "8 / (3 -", 24.0),
+            Err(ScoreError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_diagnose_answer() {
+        let cards = [3, 3, 8, 8];
+        assert_eq!(
+            diagnose_answer(&cards, "8 / (3 - 8 / 3)"),
+            Diagnosis::CorrectValue
+        );
+        assert_eq!(
+            diagnose_answer(&cards, "3 + 3 + 8 + 8"),
+            Diagnosis::WrongValue(22.0)
+        );
+        assert_eq!(
+            diagnose_answer(&cards, "8 / (3 - 3) + 8"),
+            Diagnosis::Undefined
+        );
+        assert_eq!(
+            diagnose_answer(&cards, "6 * 4"),
+            Diagnosis::WrongCards {
+                expected: vec![3, 3, 8, 8],
+                got: vec![4, 6],
+            }
+        );
+        match diagnose_answer(&cards, "8 / (3 -") {
+            Diagnosis::ParseError(e) => assert_eq!(e.message, "unexpected end of input"),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! 可选的进程内求解缓存。
+//!
+//! 服务端反复求解相同的牌时，可以用 `solve_24_cached` 代替 `solve_24`。
+//! 缓存以 `encode_hand` 的结果为键：与顺序无关，因此 `[8, 3, 8, 3]` 与
+//! `[3, 3, 8, 8]` 共用同一条缓存。1~13 中 4 张牌的组合（允许重复）只有
+//! C(16, 4) = 1820 种，即使全部缓存，条目数也有上限，不需要淘汰策略；
+//! 每条缓存的大小与该手牌的解数成正比。
+
+use crate::solve_24;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+type Cache = RwLock<HashMap<u16, Vec<String>>>;
+
+fn cache() -> &'static Cache {
+    static CACHE: OnceLock<Cache> = OnceLock::new();
+    CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// 把一手 4 张、点数在 1~13 的牌编码为与顺序无关的 `u16`。
+///
+/// 先排序，再把每张牌作为一个 4 位的十六进制数字依次拼接，
+/// 例如 `[8, 3, 3, 8]` 编码为 `0x3388`。张数或点数不符合时返回 `None`。
+pub fn encode_hand(cards: &[i32]) -> Option<u16> {
+    if cards.len() != 4 || !cards.iter().all(|c| (1..=13).contains(c)) {
+        return None;
+    }
+    let mut sorted = cards.to_vec();
+    sorted.sort();
+    Some(sorted.iter().fold(0, |code, &c| (code << 4) | c as u16))
+}
+
+/// 带缓存的 `solve_24`，可以在多个线程间共享。
+///
+/// 结果与 `solve_24` 的解集相同；命中缓存时返回第一次求解时的顺序。
+/// `encode_hand` 无法编码的牌（例如 5 张或含 0）不会被缓存，每次都直接求解。
+pub fn solve_24_cached(cards: &[i32]) -> Vec<String> {
+    let Some(key) = encode_hand(cards) else {
+        return solve_24(cards);
+    };
+    if let Some(solutions) = cache().read().unwrap().get(&key) {
+        return solutions.clone();
+    }
+    let solutions = solve_24(cards);
+    cache()
+        .write()
+        .unwrap()
+        .entry(key)
+        .or_insert(solutions)
+        .clone()
+}
+
+/// 判断这手牌的解是否已经在缓存中。
+pub fn is_cached(cards: &[i32]) -> bool {
+    encode_hand(cards).is_some_and(|key| cache().read().unwrap().contains_key(&key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_hand() {
+        assert_eq!(encode_hand(&[8, 3, 3, 8]), Some(0x3388));
+        assert_eq!(encode_hand(&[13, 1, 12, 10]), encode_hand(&[1, 10, 12, 13]));
+        assert_eq!(encode_hand(&[1, 2, 3]), None);
+        assert_eq!(encode_hand(&[0, 2, 3, 4]), None);
+    }
+
+    #[test]
+    fn test_solve_24_cached() {
+        // A hand no other test caches, so the first call is always a miss.
+        let cards = [13, 11, 7, 5];
+        assert!(!is_cached(&cards));
+        let first = solve_24_cached(&cards);
+        assert!(is_cached(&cards));
+        assert!(is_cached(&[5, 7, 11, 13]));
+        let second = solve_24_cached(&[5, 7, 11, 13]);
+        assert_eq!(first, second);
+
+        let mut expected = solve_24(&cards);
+        let mut got = first;
+        expected.sort();
+        got.sort();
+        assert_eq!(got, expected);
+    }
+}
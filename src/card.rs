@@ -0,0 +1,211 @@
+//! 带花色的扑克牌。
+//!
+//! 求解器只关心点数（`i32`）；需要区分花色或 A 的特殊规则时使用 `Card`。
+//! 牌面到点数的换算由 `CardValueMap` 决定，不同地区对 J/Q/K 的算法不同。
+
+use std::fmt;
+
+/// 花色。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    /// 全部四种花色。
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+    fn symbol(self) -> char {
+        match self {
+            Suit::Clubs => '♣',
+            Suit::Diamonds => '♦',
+            Suit::Hearts => '♥',
+            Suit::Spades => '♠',
+        }
+    }
+}
+
+/// 牌面（1~13，A = 1）到参与计算的数值的换算规则。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CardValueMap {
+    /// J/Q/K 分别算 11/12/13，这是默认规则。
+    #[default]
+    Standard,
+    /// 与二十一点相同，J/Q/K 都算 10。
+    Blackjack,
+}
+
+impl CardValueMap {
+    /// 牌面 `rank`（1~13）按本规则换算出的数值。
+    pub fn value(self, rank: u8) -> i32 {
+        match self {
+            CardValueMap::Blackjack if rank > 10 => 10,
+            _ => rank as i32,
+        }
+    }
+}
+
+/// 解析一张牌的牌面并按 `map` 换算成数值。
+///
+/// 接受 `A`/`J`/`Q`/`K`（不区分大小写）以及 `1`~`13`；数字表示牌面，
+/// 因此 `Blackjack` 规则下 `13` 与 `K` 一样算 10。无法识别时返回 `None`。
+pub fn parse_card(s: &str, map: CardValueMap) -> Option<i32> {
+    let rank = match s.trim().to_ascii_uppercase().as_str() {
+        "A" => 1,
+        "J" => 11,
+        "Q" => 12,
+        "K" => 13,
+        n => n.parse().ok().filter(|r| (1..=13).contains(r))?,
+    };
+    Some(map.value(rank))
+}
+
+/// 一手牌的张数。
+pub const HAND_SIZE: usize = 4;
+
+/// 一手牌不合法的原因，见 `validate_hand`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandError {
+    /// 牌的张数不对。
+    WrongCount { expected: usize, got: usize },
+    /// 有一张牌的数值不在 1~13 之内。
+    OutOfRange(i32),
+}
+
+impl fmt::Display for HandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandError::WrongCount { expected, got } => {
+                write!(f, "expected {} cards, got {}", expected, got)
+            }
+            HandError::OutOfRange(card) => write!(f, "card {} is not in 1..=13", card),
+        }
+    }
+}
+
+impl std::error::Error for HandError {}
+
+/// 检查 `cards` 恰好有 `expected` 张、且每张都在 1~13 之内。
+pub fn validate_cards(cards: &[i32], expected: usize) -> Result<(), HandError> {
+    if cards.len() != expected {
+        return Err(HandError::WrongCount {
+            expected,
+            got: cards.len(),
+        });
+    }
+    match cards.iter().find(|c| !(1..=13).contains(*c)) {
+        Some(&card) => Err(HandError::OutOfRange(card)),
+        None => Ok(()),
+    }
+}
+
+/// 检查 `cards` 是一手合法的牌：恰好 `HAND_SIZE` 张，每张都在 1~13 之内。
+///
+/// 求解函数按 4 张牌的结构取值，张数不对时会越界，因此接受外部输入的地方
+/// （命令行、日志文件）应先用它校验。
+pub fn validate_hand(cards: &[i32]) -> Result<(), HandError> {
+    validate_cards(cards, HAND_SIZE)
+}
+
+/// 一张牌：点数 1~13（A = 1，J/Q/K = 11/12/13）与花色。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Card {
+    rank: u8,
+    pub suit: Suit,
+}
+
+impl Card {
+    /// 构造一张牌；`rank` 不在 1~13 时返回 `None`。
+    pub fn new(rank: u8, suit: Suit) -> Option<Card> {
+        (1..=13).contains(&rank).then_some(Card { rank, suit })
+    }
+
+    pub fn rank(&self) -> u8 {
+        self.rank
+    }
+
+    pub fn is_ace(&self) -> bool {
+        self.rank == 1
+    }
+
+    /// 标准规则下参与计算的点数。
+    pub fn value(&self) -> i32 {
+        self.rank as i32
+    }
+
+    /// 按 `map` 换算出的参与计算的点数。
+    pub fn value_with(&self, map: CardValueMap) -> i32 {
+        map.value(self.rank)
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rank = match self.rank {
+            1 => "A".to_string(),
+            11 => "J".to_string(),
+            12 => "Q".to_string(),
+            13 => "K".to_string(),
+            n => n.to_string(),
+        };
+        write!(f, "{}{}", rank, self.suit.symbol())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_card() {
+        let ace = Card::new(1, Suit::Spades).unwrap();
+        assert!(ace.is_ace());
+        assert_eq!(ace.value(), 1);
+        assert_eq!(ace.to_string(), "A♠");
+        assert_eq!(Card::new(12, Suit::Hearts).unwrap().to_string(), "Q♥");
+        assert_eq!(Card::new(0, Suit::Clubs), None);
+        assert_eq!(Card::new(14, Suit::Clubs), None);
+    }
+
+    #[test]
+    fn test_card_value_map() {
+        let hand: Vec<i32> = ["K", "k", "2", "2"]
+            .iter()
+            .map(|s| parse_card(s, CardValueMap::Blackjack).unwrap())
+            .collect();
+        assert_eq!(hand, vec![10, 10, 2, 2]);
+
+        assert_eq!(parse_card("K", CardValueMap::Standard), Some(13));
+        assert_eq!(parse_card("13", CardValueMap::Blackjack), Some(10));
+        assert_eq!(parse_card("A", CardValueMap::Blackjack), Some(1));
+        assert_eq!(parse_card("14", CardValueMap::Standard), None);
+        assert_eq!(parse_card("X", CardValueMap::Standard), None);
+        let queen = Card::new(12, Suit::Hearts).unwrap();
+        assert_eq!(queen.value_with(CardValueMap::Blackjack), 10);
+    }
+
+    #[test]
+    fn test_validate_hand() {
+        assert_eq!(validate_hand(&[3, 3, 8, 8]), Ok(()));
+        assert_eq!(
+            validate_hand(&[3, 3, 8]),
+            Err(HandError::WrongCount {
+                expected: 4,
+                got: 3
+            })
+        );
+        assert_eq!(
+            validate_hand(&[3, 3, 8, 14]),
+            Err(HandError::OutOfRange(14))
+        );
+        assert_eq!(validate_hand(&[0, 3, 8, 8]), Err(HandError::OutOfRange(0)));
+        assert_eq!(validate_cards(&[1, 2, 3], 3), Ok(()));
+        assert_eq!(
+            validate_hand(&[1, 2, 3, 4, 5]).unwrap_err().to_string(),
+            "expected 4 cards, got 5"
+        );
+    }
+}
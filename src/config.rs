@@ -0,0 +1,258 @@
+//! 求解器配置。
+//!
+//! 标准规则是"四种运算、目标 24、绝对误差 `EPSILON`"；`SolverConfig`
+//! 把这些取值集中在一起，便于各种变体规则复用同一套枚举逻辑。
+
+use crate::logging::LogFormat;
+use crate::{solve_with_config, EPSILON, OPERATORS, TARGET};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+/// 判断计算结果是否"等于"目标值的方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Comparison {
+    /// `|result - target| < epsilon`。目标值较小（如标准的 24）时使用，这是默认值。
+    Absolute,
+    /// `|result - target| / max(|target|, 1) < epsilon`。
+    ///
+    /// 目标值很大时，`f64` 的舍入误差会随数值放大，固定的绝对误差会把
+    /// 本应成立的解误判为不成立，此时应改用相对误差。
+    Relative,
+}
+
+/// 除法的计算方式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DivisionMode {
+    /// 普通除法，允许分数结果，这是默认值。
+    #[default]
+    Exact,
+    /// 向下取整的整数除法，即 `(a / b).floor()`，与许多编程语言的 `//` 相同。
+    Floor,
+    /// 四舍五入到最近的整数（`.5` 远离 0）。
+    Round,
+}
+
+/// 求解器的可调参数，`Default` 即标准 24 点规则。
+///
+/// 可以用 `load_config` / `save_config` 读写 TOML 文件；文件中缺省的字段取默认值。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SolverConfig {
+    /// 需要凑出的目标值。
+    pub target: f64,
+    /// 判断结果与目标相等时的容差（除法判零仍使用 `EPSILON`）。
+    pub epsilon: f64,
+    /// 允许使用的运算符。
+    pub ops: Vec<char>,
+    /// 误差的比较方式。
+    pub comparison: Comparison,
+    /// 为 `true` 时要求每一步中间结果都是整数：改用 `i64` 精确计算，
+    /// 除不尽或溢出的路径直接放弃，结果必须精确等于（整数）目标值，
+    /// 此时 `epsilon` 与 `comparison` 不起作用。
+    pub integer_intermediates: bool,
+    /// 除法的计算方式；除数接近 0 时无论哪种方式都放弃该路径。
+    /// 与 `integer_intermediates` 同时使用时不起作用，整数模式只接受能整除的除法。
+    pub division: DivisionMode,
+    /// 命令行程序写文本日志时使用的格式，对应 TOML 中的 `[log]` 表，不影响求解。
+    pub log: LogFormat,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            target: TARGET,
+            epsilon: EPSILON,
+            ops: OPERATORS.to_vec(),
+            comparison: Comparison::Absolute,
+            integer_intermediates: false,
+            division: DivisionMode::Exact,
+            log: LogFormat::default(),
+        }
+    }
+}
+
+impl SolverConfig {
+    /// 判断 `value` 是否按本配置等于目标值。
+    pub fn matches(&self, value: f64) -> bool {
+        let error = (value - self.target).abs();
+        match self.comparison {
+            Comparison::Absolute => error < self.epsilon,
+            Comparison::Relative => error / self.target.abs().max(1.0) < self.epsilon,
+        }
+    }
+}
+
+/// 以链式调用的方式构造 `SolverConfig` 并求解。
+///
+/// 未设置的项取 `SolverConfig::default()` 的值：
+///
+/// ```rust
+/// use twelve_four_puzzle::config::Solver;
+///
+/// let solutions = Solver::new().target(36.0).ops(&['+', '*']).solve(&[1, 2, 3, 4]);
+/// assert!(solutions.contains(&"((1 + 2) * 3) * 4".to_string()));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Solver {
+    config: SolverConfig,
+}
+
+impl Solver {
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    pub fn target(mut self, target: f64) -> Solver {
+        self.config.target = target;
+        self
+    }
+
+    pub fn epsilon(mut self, epsilon: f64) -> Solver {
+        self.config.epsilon = epsilon;
+        self
+    }
+
+    pub fn ops(mut self, ops: &[char]) -> Solver {
+        self.config.ops = ops.to_vec();
+        self
+    }
+
+    pub fn comparison(mut self, comparison: Comparison) -> Solver {
+        self.config.comparison = comparison;
+        self
+    }
+
+    pub fn integer_intermediates(mut self, enabled: bool) -> Solver {
+        self.config.integer_intermediates = enabled;
+        self
+    }
+
+    pub fn division(mut self, mode: DivisionMode) -> Solver {
+        self.config.division = mode;
+        self
+    }
+
+    /// 构造出的配置。
+    pub fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    /// 等价于 `solve_with_config(cards, self.config())`。
+    pub fn solve(&self, cards: &[i32]) -> Vec<String> {
+        solve_with_config(cards, &self.config)
+    }
+}
+
+/// 从 TOML 文件读取 `SolverConfig`。
+///
+/// 文件内容不是合法配置、或 `[log]` 中的时间戳格式不合法（见 `LogFormat::validate`）时
+/// 返回 `io::ErrorKind::InvalidData` 错误。
+pub fn load_config(path: impl AsRef<Path>) -> io::Result<SolverConfig> {
+    let text = std::fs::read_to_string(path)?;
+    let config: SolverConfig =
+        toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    config
+        .log
+        .validate()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(config)
+}
+
+/// 把 `SolverConfig` 以 TOML 格式写入文件（覆盖已有内容）。
+pub fn save_config(path: impl AsRef<Path>, config: &SolverConfig) -> io::Result<()> {
+    let text =
+        toml::to_string(config).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    std::fs::write(path, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_absolute_and_relative() {
+        let config = SolverConfig::default();
+        assert!(config.matches(24.0 + 1e-9));
+        assert!(!config.matches(24.001));
+
+        let config = SolverConfig {
+            target: 1e12,
+            comparison: Comparison::Relative,
+            ..SolverConfig::default()
+        };
+        assert!(config.matches(1e12 + 0.5));
+        assert!(!config.matches(1e12 + 1e7));
+    }
+
+    #[test]
+    fn test_config_toml_round_trip() {
+        let config = SolverConfig {
+            target: 36.0,
+            ops: vec!['+', '*'],
+            comparison: Comparison::Relative,
+            division: DivisionMode::Floor,
+            log: LogFormat {
+                timestamp_format: "%d/%m/%Y".to_string(),
+                ..LogFormat::default()
+            },
+            ..SolverConfig::default()
+        };
+        let path = std::env::temp_dir().join(format!("solver-{}.toml", std::process::id()));
+        save_config(&path, &config).unwrap();
+        let loaded = load_config(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, config);
+    }
+
+    #[test]
+    fn test_config_missing_fields_use_defaults() {
+        let config: SolverConfig = toml::from_str("target = 36.0").unwrap();
+        assert_eq!(config.target, 36.0);
+        assert_eq!(config.ops, OPERATORS.to_vec());
+        assert!(toml::from_str::<SolverConfig>("target = \"x\"").is_err());
+        assert_eq!(config.log, LogFormat::default());
+    }
+
+    #[test]
+    fn test_load_config_rejects_invalid_timestamp_format() {
+        let path = std::env::temp_dir().join(format!("solver-log-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "target = 24.0\n[log]\ntimestamp_format = \"%Y-%Q\"\n",
+        )
+        .unwrap();
+        let err = load_config(&path).unwrap_err();
+        std::fs::write(&path, "[log]\ntimestamp_format = \"%d/%m\"\n").unwrap();
+        let loaded = load_config(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("%Y-%Q"), "{}", err);
+        assert_eq!(loaded.log.timestamp_format, "%d/%m");
+    }
+
+    #[test]
+    fn test_solver_builder() {
+        let solver = Solver::new()
+            .target(36.0)
+            .epsilon(1e-9)
+            .ops(&['+', '*'])
+            .integer_intermediates(true);
+        assert_eq!(solver.config().target, 36.0);
+        assert_eq!(solver.config().comparison, Comparison::Absolute);
+
+        let solutions = solver.solve(&[1, 2, 3, 4]);
+        assert!(!solutions.is_empty());
+        for s in &solutions {
+            let expr = crate::expr::parse_expr(s).unwrap();
+            assert_eq!(crate::expr::eval(&expr), Some(36.0), "{}", s);
+            assert!(expr.operators().iter().all(|op| matches!(op, '+' | '*')));
+        }
+        assert_eq!(
+            Solver::new().solve(&[3, 3, 8, 8]),
+            crate::solve_24(&[3, 3, 8, 8])
+        );
+    }
+}
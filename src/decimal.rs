@@ -0,0 +1,125 @@
+//! 定点小数运算。
+//!
+//! 介于 `f64` 与 `Rational` 之间的折中：数值以 `i64` 存储、固定保留
+//! 12 位小数，所有运算都是整数运算，因此在任何平台上结果都完全一致；
+//! 代价是 `8 / 3` 这样的除法会被舍入，判断是否等于目标时需要一点容差。
+
+use crate::{eval_struct_decimal, solve_by, TARGET};
+use std::fmt;
+
+/// 小数位数对应的缩放因子：`Decimal` 的内部值等于实际值乘以 `SCALE`。
+///
+/// 4 张 1~13 的牌算出的中间值绝对值远小于 `i64::MAX / SCALE`（约 9.2e6），不会溢出。
+pub const SCALE: i64 = 1_000_000_000_000;
+
+/// 判断结果等于目标值时允许的误差（内部单位，即 `1e-6`，与 `EPSILON` 相同）。
+///
+/// 每次除法最多引入半个最小单位（`5e-13`）的舍入误差，后续的乘除会把它放大，
+/// 连续取倒数时放大得最多：`1 / (1 / (2 * 12))` 的误差约为 `24² * 5e-13`，
+/// 仍比容差小三个数量级；而 4 张 1~13 的牌能算出的其他值与 24 的差距远大于容差，
+/// 不会被误判为 24。测试用 `solve_24_exact` 在全部 1820 种牌型上核对了结果完全一致。
+pub const TOLERANCE: i64 = 1_000_000;
+
+/// 保留 6 位小数的定点数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Decimal(i64);
+
+// Integer division rounding half away from zero.
+fn div_round(num: i128, den: i128) -> i128 {
+    let quotient = num / den;
+    let remainder = num % den;
+    if 2 * remainder.abs() >= den.abs() {
+        quotient + if (num < 0) == (den < 0) { 1 } else { -1 }
+    } else {
+        quotient
+    }
+}
+
+impl Decimal {
+    /// 整数 `n` 对应的定点数。
+    pub fn from_int(n: i64) -> Decimal {
+        Decimal(n * SCALE)
+    }
+
+    /// 由内部值（实际值乘以 `SCALE`）构造。
+    pub fn from_raw(raw: i64) -> Decimal {
+        Decimal(raw)
+    }
+
+    pub fn raw(&self) -> i64 {
+        self.0
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.0 as f64 / SCALE as f64
+    }
+
+    /// 与 `apply_op` 对应的定点运算：乘除的结果四舍五入到最小单位，
+    /// 除以 0、未知运算符或溢出时返回 `None`。
+    pub fn apply(self, other: Decimal, op: char) -> Option<Decimal> {
+        let (a, b) = (self.0 as i128, other.0 as i128);
+        let value = match op {
+            '+' => a + b,
+            '-' => a - b,
+            '*' => div_round(a * b, SCALE as i128),
+            '/' if b != 0 => div_round(a * SCALE as i128, b),
+            _ => return None,
+        };
+        i64::try_from(value).ok().map(Decimal)
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let scale = SCALE as u64;
+        write!(f, "{}{}.{:012}", sign, abs / scale, abs % scale)
+    }
+}
+
+/// 用定点小数运算求 24，结果与 24 相差不超过 `TOLERANCE` 即视为成立。
+pub fn solve_24_decimal(cards: &[i32]) -> Vec<String> {
+    let target = Decimal::from_int(TARGET as i64);
+    solve_by(cards, eval_struct_decimal, |v| {
+        (v.raw() - target.raw()).abs() <= TOLERANCE
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rational::solve_24_exact;
+    use crate::survey::all_hands;
+
+    #[test]
+    fn test_decimal_arithmetic() {
+        let eight = Decimal::from_int(8);
+        let three = Decimal::from_int(3);
+        assert_eq!(
+            eight.apply(three, '/').unwrap().to_string(),
+            "2.666666666667"
+        );
+        assert_eq!(
+            three.apply(eight, '-').unwrap().to_string(),
+            "-5.000000000000"
+        );
+        assert_eq!(eight.apply(Decimal::from_int(0), '/'), None);
+        let third = three.apply(eight.apply(three, '/').unwrap(), '-').unwrap();
+        assert_eq!(
+            eight.apply(third, '/').unwrap(),
+            Decimal::from_raw(24_000_000_000_024)
+        );
+    }
+
+    #[test]
+    fn test_solve_24_decimal_agrees_with_rational() {
+        for cards in all_hands() {
+            let mut decimal = solve_24_decimal(&cards);
+            let mut exact = solve_24_exact(&cards);
+            decimal.sort();
+            exact.sort();
+            assert_eq!(decimal, exact, "{:?}", cards);
+        }
+    }
+}
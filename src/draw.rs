@@ -0,0 +1,236 @@
+//! 随机抽牌。
+//!
+//! 抽牌函数都接受外部传入的随机数生成器，这样调用方可以用 `thread_rng()`
+//! 得到真正随机的牌，也可以用带种子的 `StdRng` 得到可复现的结果。
+
+use crate::analysis::{difficulty, is_fair};
+use crate::card::HAND_SIZE;
+use crate::is_solvable;
+use chrono::NaiveDate;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// 从 1~13 中不重复地随机抽取 4 张牌。
+///
+/// 每个点数只有一张，因此抽到的 4 张牌点数互不相同。
+pub fn draw(rng: &mut impl Rng) -> Vec<i32> {
+    let mut cards = (1..=13).collect::<Vec<i32>>();
+    cards.shuffle(rng);
+    cards.into_iter().take(4).collect()
+}
+
+/// 模拟一副 52 张的真实扑克牌（每个点数 4 张），不放回地抽取 4 张。
+///
+/// 与 `draw` 不同，这里同一点数最多可以抽到 4 次，例如 `[6, 6, 6, 6]`，
+/// 各种牌型出现的概率也与真实发牌一致。
+pub fn draw_from_deck(rng: &mut impl Rng) -> Vec<i32> {
+    let mut deck: Vec<i32> = (1..=13).flat_map(|rank| [rank; 4]).collect();
+    deck.shuffle(rng);
+    deck.into_iter().take(4).collect()
+}
+
+/// 反复调用 `draw` 直到抽到一手有解的牌。
+///
+/// 1~13 中绝大多数四张牌的组合都有解，因此通常一两次就能返回。
+pub fn draw_solvable_hand(rng: &mut impl Rng) -> Vec<i32> {
+    loop {
+        let hand = draw(rng);
+        if is_solvable(&hand) {
+            return hand;
+        }
+    }
+}
+
+/// 带筛选条件的抽牌函数（如 `draw_division_hand`）最多尝试的次数。
+pub const MAX_DRAW_ATTEMPTS: usize = 10_000;
+
+/// 抽一手"必须用除法"的牌：有解，但 `solve_no_division` 找不到解。
+///
+/// 适合除法专题练习。这类牌在 1~13 中比较少见，因此最多调用
+/// `MAX_DRAW_ATTEMPTS` 次 `draw_solvable_hand`，仍未抽到时返回 `None`。
+pub fn draw_division_hand(rng: &mut impl Rng) -> Option<Vec<i32>> {
+    (0..MAX_DRAW_ATTEMPTS)
+        .map(|_| draw_solvable_hand(rng))
+        .find(|hand| !is_fair(hand))
+}
+
+/// 抽一手有解、且至少有 `min_distinct` 种不同点数的牌，避免 `[6, 6, 6, 6]` 这类单调的题目。
+///
+/// `draw` 抽到的牌点数本来就互不相同，这里改用 `draw_from_deck` 从整副牌中抽，
+/// 最多尝试 `MAX_DRAW_ATTEMPTS` 次，仍不满足时返回 `None`；
+/// `min_distinct` 超过 `HAND_SIZE` 时不可能满足，不抽牌直接返回 `None`。
+pub fn draw_hand_distinct(rng: &mut impl Rng, min_distinct: usize) -> Option<Vec<i32>> {
+    if min_distinct > HAND_SIZE {
+        return None;
+    }
+    (0..MAX_DRAW_ATTEMPTS)
+        .map(|_| draw_from_deck(rng))
+        .find(|hand| {
+            let mut values = hand.clone();
+            values.sort();
+            values.dedup();
+            values.len() >= min_distinct && is_solvable(hand)
+        })
+}
+
+/// `challenge_ladder` 每一级抽取的候选牌数。
+const LADDER_POOL_FACTOR: usize = 4;
+
+/// 生成 `n` 手有解的牌，按 `DifficultyMetrics::hardness_key` 从易到难排列。
+///
+/// 先用 `draw_solvable_hand` 抽 `n * LADDER_POOL_FACTOR` 手候选牌并按难度排序，
+/// 再从中等间隔地取 `n` 手：第一手是候选中最简单的，最后一手是最难的，
+/// 中间各级的难度大致均匀递增，而不是随机抽 `n` 手再排序那样忽高忽低。
+/// 候选之间可能重复，因此相邻两级偶尔会是同一手牌。
+pub fn challenge_ladder(n: usize, rng: &mut impl Rng) -> Vec<Vec<i32>> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut pool: Vec<Vec<i32>> = (0..n * LADDER_POOL_FACTOR)
+        .map(|_| draw_solvable_hand(rng))
+        .collect();
+    // Solvable hands always have metrics.
+    pool.sort_by_key(|hand| difficulty(hand).map(|m| m.hardness_key()));
+    if n == 1 {
+        return vec![pool.swap_remove(0)];
+    }
+    (0..n)
+        .map(|i| pool[i * (pool.len() - 1) / (n - 1)].clone())
+        .collect()
+}
+
+// 64-bit FNV-1a, fixed by its spec and so stable across platforms and crate versions.
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes
+        .into_iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+/// 根据日期确定性地生成"每日一题"，同一天所有人拿到的是同一手有解的牌。
+///
+/// 不经过 `rand`：`StdRng` 的算法可能随 `rand` 版本改变，这里直接由
+/// 日期的 ISO 字符串（如 `2025-11-18`）加上尝试序号做 FNV-1a 哈希，
+/// 用哈希值像 `draw` 一样从 1~13 中取 4 个不同点数，无解就换下一个序号。
+/// 结果因此只取决于日期，不随平台或依赖版本变化。
+pub fn daily_hand(date: NaiveDate) -> Vec<i32> {
+    let day = date.format("%Y-%m-%d").to_string();
+    (0u32..)
+        .map(|attempt| {
+            let mut hash = fnv1a(day.bytes().chain(attempt.to_le_bytes()));
+            // A partial Fisher-Yates shuffle of 1..=13 driven by the hash.
+            let mut ranks: Vec<i32> = (1..=13).collect();
+            for i in 0..4 {
+                let remaining = (ranks.len() - i) as u64;
+                ranks.swap(i, i + (hash % remaining) as usize);
+                hash /= remaining;
+            }
+            ranks.truncate(4);
+            ranks
+        })
+        .find(|hand| is_solvable(hand))
+        .expect("most hands are solvable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn test_draw_four_distinct_cards() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let hand = draw(&mut rng);
+            assert_eq!(hand.len(), 4);
+            assert!(hand.iter().all(|c| (1..=13).contains(c)));
+            assert_eq!(hand.iter().collect::<HashSet<_>>().len(), 4);
+        }
+    }
+
+    #[test]
+    fn test_draw_from_deck_respects_rank_copies() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut saw_repeat = false;
+        for _ in 0..2000 {
+            let hand = draw_from_deck(&mut rng);
+            assert_eq!(hand.len(), 4);
+            let mut counts = HashMap::new();
+            for &card in &hand {
+                assert!((1..=13).contains(&card));
+                *counts.entry(card).or_insert(0) += 1;
+            }
+            assert!(counts.values().all(|&n| n <= 4));
+            saw_repeat |= counts.len() < 4;
+        }
+        // Unlike `draw`, a real deck deals repeated ranks fairly often.
+        assert!(saw_repeat);
+    }
+
+    #[test]
+    fn test_draw_solvable_hand() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            assert!(is_solvable(&draw_solvable_hand(&mut rng)));
+        }
+    }
+
+    #[test]
+    fn test_daily_hand_is_stable() {
+        let date = NaiveDate::from_ymd_opt(2025, 11, 18).unwrap();
+        let hand = daily_hand(date);
+        assert_eq!(hand, daily_hand(date));
+        assert!(is_solvable(&hand));
+        // Pinned: the first candidate [10, 8, 3, 5] has no solution, so the
+        // second one is the puzzle of the day.
+        assert!(!is_solvable(&[10, 8, 3, 5]));
+        assert_eq!(hand, vec![1, 12, 5, 4]);
+
+        let next = NaiveDate::from_ymd_opt(2025, 11, 19).unwrap();
+        assert_ne!(daily_hand(next), hand);
+    }
+
+    #[test]
+    fn test_draw_division_hand() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..5 {
+            let hand = draw_division_hand(&mut rng).unwrap();
+            assert!(crate::solve_no_division(&hand).is_empty(), "{:?}", hand);
+            assert!(!crate::solve_24(&hand).is_empty(), "{:?}", hand);
+        }
+    }
+
+    #[test]
+    fn test_draw_hand_distinct() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for min_distinct in [2, 3, 4] {
+            for _ in 0..20 {
+                let hand = draw_hand_distinct(&mut rng, min_distinct).unwrap();
+                assert!(hand.iter().collect::<HashSet<_>>().len() >= min_distinct);
+                assert!(is_solvable(&hand));
+            }
+        }
+        // An impossible request returns at once without consuming the rng.
+        let mut untouched = rng.clone();
+        assert_eq!(draw_hand_distinct(&mut rng, 5), None);
+        assert_eq!(rng.gen::<u64>(), untouched.gen::<u64>());
+    }
+
+    #[test]
+    fn test_challenge_ladder() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let ladder = challenge_ladder(8, &mut rng);
+        assert_eq!(ladder.len(), 8);
+        let keys: Vec<_> = ladder
+            .iter()
+            .map(|hand| difficulty(hand).unwrap().hardness_key())
+            .collect();
+        assert!(keys.windows(2).all(|w| w[0] <= w[1]), "{:?}", ladder);
+        assert!(keys[0] < keys[7]);
+        assert_eq!(challenge_ladder(1, &mut rng).len(), 1);
+        assert!(challenge_ladder(0, &mut rng).is_empty());
+    }
+}
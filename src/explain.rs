@@ -0,0 +1,172 @@
+//! 把一个解拆成逐步计算的过程，便于讲解。
+
+use crate::expr::Expr;
+use crate::solution::solution_trees_iter;
+use crate::{apply_op, EPSILON};
+
+/// 格式化一个中间值：与整数相差不到 `EPSILON` 时按整数显示，
+/// 否则 `decimals` 为 `Some(n)` 时保留 `n` 位小数，为 `None` 时按 `f64` 原样显示。
+fn format_value(value: f64, decimals: Option<usize>) -> String {
+    if (value - value.round()).abs() < EPSILON {
+        return format!("{}", value.round());
+    }
+    match decimals {
+        Some(n) => format!("{:.*}", n, value),
+        None => value.to_string(),
+    }
+}
+
+/// 按计算顺序（后序）列出表达式的每一步，例如 `8 / (3 - (8 / 3))` 得到
+/// `["8 / 3 = 2.6666666666666665", "3 - 2.6666666666666665 = 0.3333333333333335", ...]`。
+///
+/// 任何一步无法计算（除数接近 0）时返回 `None`。
+pub fn explain(expr: &Expr) -> Option<Vec<String>> {
+    explain_with(expr, None)
+}
+
+/// 与 `explain` 相同，但非整数的中间值只显示 `decimals` 位小数。
+///
+/// 舍入只影响显示：每一步仍用上一步的完整精度计算，因此最后一步依然是 24。
+pub fn explain_rounded(expr: &Expr, decimals: usize) -> Option<Vec<String>> {
+    explain_with(expr, Some(decimals))
+}
+
+/// 返回枚举到的第一个解及其逐步计算过程，找到后立即停止搜索。
+///
+/// 只需要"给出一种算法"时使用：与先 `solve_24` 再挑一个相比，不会枚举剩余的组合，
+/// 也不会格式化其它解。无解时返回 `None`。
+pub fn first_solution_with_trace(cards: &[i32]) -> Option<(String, Vec<String>)> {
+    let expr = solution_trees_iter(cards).next()?;
+    let steps = explain(&expr)?;
+    Some((expr.to_string(), steps))
+}
+
+/// 以表格形式列出每一步二元运算：`(左操作数, 运算符, 右操作数, 结果)`。
+///
+/// 行的顺序与 `explain` 相同（后序）；操作数写作对应子表达式的 `Display` 形式，
+/// 例如 `8 / (3 - (8 / 3))` 的最后一行是 `("8", '/', "3 - (8 / 3)", 24.0)`。
+/// 结果保留完整精度，便于前端自行排版。一元负号与绝对值不单独成行。
+/// 任何一步无法计算（除数接近 0）时返回 `None`。
+pub fn explain_table(expr: &Expr) -> Option<Vec<(String, char, String, f64)>> {
+    fn walk(expr: &Expr, rows: &mut Vec<(String, char, String, f64)>) -> Option<f64> {
+        match expr {
+            Expr::Num(v) => Some(*v),
+            Expr::Neg(inner) => Some(-walk(inner, rows)?),
+            Expr::Abs(inner) => Some(walk(inner, rows)?.abs()),
+            Expr::Bin(op, lhs, rhs) => {
+                let a = walk(lhs, rows)?;
+                let b = walk(rhs, rows)?;
+                let value = apply_op(a, b, *op)?;
+                rows.push((lhs.to_string(), *op, rhs.to_string(), value));
+                Some(value)
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    walk(expr, &mut rows)?;
+    Some(rows)
+}
+
+fn explain_with(expr: &Expr, decimals: Option<usize>) -> Option<Vec<String>> {
+    fn walk(expr: &Expr, decimals: Option<usize>, steps: &mut Vec<String>) -> Option<f64> {
+        match expr {
+            Expr::Num(v) => Some(*v),
+            Expr::Neg(inner) => {
+                let v = walk(inner, decimals, steps)?;
+                steps.push(format!(
+                    "-({}) = {}",
+                    format_value(v, decimals),
+                    format_value(-v, decimals)
+                ));
+                Some(-v)
+            }
+            Expr::Abs(inner) => {
+                let v = walk(inner, decimals, steps)?;
+                steps.push(format!(
+                    "|{}| = {}",
+                    format_value(v, decimals),
+                    format_value(v.abs(), decimals)
+                ));
+                Some(v.abs())
+            }
+            Expr::Bin(op, lhs, rhs) => {
+                let a = walk(lhs, decimals, steps)?;
+                let b = walk(rhs, decimals, steps)?;
+                let value = apply_op(a, b, *op)?;
+                steps.push(format!(
+                    "{} {} {} = {}",
+                    format_value(a, decimals),
+                    op,
+                    format_value(b, decimals),
+                    format_value(value, decimals)
+                ));
+                Some(value)
+            }
+        }
+    }
+
+    let mut steps = Vec::new();
+    walk(expr, decimals, &mut steps)?;
+    Some(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::parse_expr;
+
+    #[test]
+    fn test_explain() {
+        let expr = parse_expr("(6 * 2) + (3 * 4)").unwrap();
+        assert_eq!(
+            explain(&expr),
+            Some(vec![
+                "6 * 2 = 12".to_string(),
+                "3 * 4 = 12".to_string(),
+                "12 + 12 = 24".to_string(),
+            ])
+        );
+        assert_eq!(explain(&parse_expr("1 / (2 - 2)").unwrap()), None);
+    }
+
+    #[test]
+    fn test_explain_rounded() {
+        let expr = parse_expr("8 / (3 - 8 / 3)").unwrap();
+        let full = explain(&expr).unwrap();
+        assert!(full[0].starts_with("8 / 3 = 2.666666"));
+        assert_eq!(
+            explain_rounded(&expr, 2).unwrap(),
+            vec!["8 / 3 = 2.67", "3 - 2.67 = 0.33", "8 / 0.33 = 24"]
+        );
+    }
+
+    #[test]
+    fn test_first_solution_with_trace() {
+        let (solution, steps) = first_solution_with_trace(&[3, 3, 8, 8]).unwrap();
+        assert_eq!(solution, "8 / (3 - (8 / 3))");
+        assert_eq!(steps.len(), 3);
+        assert!(steps.last().unwrap().ends_with("= 24"), "{:?}", steps);
+        assert_eq!(first_solution_with_trace(&[1, 1, 1, 1]), None);
+    }
+
+    #[test]
+    fn test_explain_table() {
+        let expr = parse_expr("8 / (3 - (8 / 3))").unwrap();
+        let rows = explain_table(&expr).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            (rows[0].0.as_str(), rows[0].1, rows[0].2.as_str()),
+            ("8", '/', "3")
+        );
+        assert_eq!(
+            (rows[1].0.as_str(), rows[1].1, rows[1].2.as_str()),
+            ("3", '-', "8 / 3")
+        );
+        assert_eq!(rows[2].2, "3 - (8 / 3)");
+        assert!((rows[0].3 - 8.0 / 3.0).abs() < EPSILON);
+        assert!((rows[1].3 - 1.0 / 3.0).abs() < EPSILON);
+        assert!((rows[2].3 - 24.0).abs() < EPSILON);
+        assert_eq!(explain_table(&parse_expr("8 / (3 - 3)").unwrap()), None);
+    }
+}
@@ -0,0 +1,809 @@
+//! 表达式树：求值、格式化与解析。
+//!
+//! `Expr` 是求解器与用户输入共用的表达式表示：叶子是数字，内部节点是
+//! 二元运算（运算符仍用 `char` 表示，与 `apply_op` 保持一致），
+//! 以及一元负号和绝对值。
+//! `Display` 的输出格式与求解器生成的字符串完全一致：二元运算的子表达式
+//! 如果本身也是二元运算，就加一层括号，顶层不加括号，例如 `(6 * 2) + (3 * 4)`。
+
+use crate::apply_op;
+use std::fmt;
+
+/// 一棵算式树。
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// 数字叶子。
+    Num(f64),
+    /// 一元负号，例如 `-(1 + 2)`。
+    Neg(Box<Expr>),
+    /// 绝对值，写作 `|3 - 8|`；标准求解器不会产生，见 `variants::solve_with_abs`。
+    Abs(Box<Expr>),
+    /// 二元运算：运算符、左操作数、右操作数。
+    Bin(char, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// 构造二元运算节点的便捷函数。
+    pub fn bin(op: char, lhs: Expr, rhs: Expr) -> Expr {
+        Expr::Bin(op, Box::new(lhs), Box::new(rhs))
+    }
+
+    /// 树的深度：叶子为 0，每层二元运算加 1。
+    ///
+    /// 四张牌的解中，`(a op b) op (c op d)` 深度为 2，其余四种括号形态深度为 3。
+    pub fn depth(&self) -> usize {
+        match self {
+            Expr::Num(_) => 0,
+            Expr::Neg(inner) | Expr::Abs(inner) => inner.depth(),
+            Expr::Bin(_, lhs, rhs) => 1 + lhs.depth().max(rhs.depth()),
+        }
+    }
+
+    /// 按从左到右（中序）的顺序列出树中所有二元运算符。
+    pub fn operators(&self) -> Vec<char> {
+        match self {
+            Expr::Num(_) => Vec::new(),
+            Expr::Neg(inner) | Expr::Abs(inner) => inner.operators(),
+            Expr::Bin(op, lhs, rhs) => {
+                let mut ops = lhs.operators();
+                ops.push(*op);
+                ops.extend(rhs.operators());
+                ops
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Num(v) => write!(f, "{}", v),
+            Expr::Neg(inner) => {
+                write!(f, "-")?;
+                fmt_operand(inner, f)
+            }
+            // The bars already group their contents, so no parentheses inside.
+            Expr::Abs(inner) => write!(f, "|{}|", inner),
+            Expr::Bin(op, lhs, rhs) => {
+                fmt_operand(lhs, f)?;
+                write!(f, " {} ", op)?;
+                fmt_operand(rhs, f)
+            }
+        }
+    }
+}
+
+// Operands that are themselves binary operations are always parenthesized,
+// which reproduces the strings built by the `try_struct*` helpers.
+fn fmt_operand(expr: &Expr, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match expr {
+        Expr::Bin(..) => write!(f, "({})", expr),
+        _ => write!(f, "{}", expr),
+    }
+}
+
+/// `tokenize_solution` 产生的记号。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token {
+    Num(f64),
+    /// 二元运算符。
+    Op(char),
+    /// 一元负号，紧贴在后面的操作数之前。
+    Neg,
+    /// 绝对值的竖线，开头和结尾都是这个记号。
+    Bar,
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    // Binary operators carry the surrounding spaces, so concatenating the
+    // tokens reproduces the `Display` output of the tree.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Num(v) => write!(f, "{}", v),
+            Token::Op(op) => write!(f, " {} ", op),
+            Token::Neg => write!(f, "-"),
+            Token::Bar => write!(f, "|"),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+        }
+    }
+}
+
+/// 把表达式树展开为记号序列，括号的位置与 `Display` 完全一致。
+///
+/// 供前端自行排版使用，不需要再解析字符串；把每个记号的 `Display` 依次拼接
+/// 就得到 `expr.to_string()`。
+pub fn tokenize_solution(expr: &Expr) -> Vec<Token> {
+    fn operand(expr: &Expr, out: &mut Vec<Token>) {
+        match expr {
+            Expr::Bin(..) => {
+                out.push(Token::LParen);
+                walk(expr, out);
+                out.push(Token::RParen);
+            }
+            _ => walk(expr, out),
+        }
+    }
+
+    fn walk(expr: &Expr, out: &mut Vec<Token>) {
+        match expr {
+            Expr::Num(v) => out.push(Token::Num(*v)),
+            Expr::Neg(inner) => {
+                out.push(Token::Neg);
+                operand(inner, out);
+            }
+            Expr::Abs(inner) => {
+                out.push(Token::Bar);
+                walk(inner, out);
+                out.push(Token::Bar);
+            }
+            Expr::Bin(op, lhs, rhs) => {
+                operand(lhs, out);
+                out.push(Token::Op(*op));
+                operand(rhs, out);
+            }
+        }
+    }
+
+    let mut tokens = Vec::new();
+    walk(expr, &mut tokens);
+    tokens
+}
+
+/// 对表达式树求值。
+///
+/// 与求解器使用同一个 `apply_op`，因此除数接近 0 时整棵树的结果为 `None`。
+pub fn eval(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Num(v) => Some(*v),
+        Expr::Neg(inner) => eval(inner).map(|v| -v),
+        Expr::Abs(inner) => eval(inner).map(f64::abs),
+        Expr::Bin(op, lhs, rhs) => apply_op(eval(lhs)?, eval(rhs)?, *op),
+    }
+}
+
+/// 返回表达式的规范形式：把加法和乘法的两个操作数按其字符串排序。
+///
+/// 交换律等价的表达式（如 `6 * 4` 与 `4 * 6`、`(1 + 2) * 8` 与 `8 * (2 + 1)`）
+/// 规范化后完全相同，因此可以用规范形式的字符串作为去重的键。
+/// 这里只处理交换律，不处理结合律（`(a + b) + c` 与 `a + (b + c)` 仍视为不同）。
+pub fn canonical(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Num(v) => Expr::Num(*v),
+        Expr::Neg(inner) => Expr::Neg(Box::new(canonical(inner))),
+        Expr::Abs(inner) => Expr::Abs(Box::new(canonical(inner))),
+        Expr::Bin(op, lhs, rhs) => {
+            let (lhs, rhs) = (canonical(lhs), canonical(rhs));
+            if matches!(op, '+' | '*') && operand_key(&rhs) < operand_key(&lhs) {
+                Expr::bin(*op, rhs, lhs)
+            } else {
+                Expr::bin(*op, lhs, rhs)
+            }
+        }
+    }
+}
+
+// Sort key for `canonical`: the operand exactly as it appears inside its
+// parent, so parenthesized sub-expressions order before plain numbers.
+fn operand_key(expr: &Expr) -> String {
+    match expr {
+        Expr::Bin(..) => format!("({})", expr),
+        _ => expr.to_string(),
+    }
+}
+
+/// 把表达式渲染为逆波兰（后缀）表示，例如 `6 3 - 2 6 + *`。
+///
+/// 逆波兰表示不需要括号；一元负号与绝对值分别写作 `neg`、`abs`，作用于栈顶元素。
+pub fn to_rpn(expr: &Expr) -> String {
+    match expr {
+        Expr::Num(v) => v.to_string(),
+        Expr::Neg(inner) => format!("{} neg", to_rpn(inner)),
+        Expr::Abs(inner) => format!("{} abs", to_rpn(inner)),
+        Expr::Bin(op, lhs, rhs) => format!("{} {} {}", to_rpn(lhs), to_rpn(rhs), op),
+    }
+}
+
+/// 把表达式树导出为 Graphviz DOT 格式，可以直接交给 `dot -Tpng` 渲染。
+///
+/// 每个节点（运算符与数字叶子）按先序编号为 `n0`、`n1`……，根节点是 `n0`；
+/// 每条边从父节点指向子节点，左子节点的边先于右子节点写出，`dot` 会按此顺序
+/// 从左到右排列。一元负号与绝对值的节点标签分别为 `neg`、`abs`。
+pub fn to_dot(expr: &Expr) -> String {
+    fn walk(expr: &Expr, next: &mut usize, out: &mut String) -> usize {
+        let id = *next;
+        *next += 1;
+        let label = match expr {
+            Expr::Num(v) => v.to_string(),
+            Expr::Neg(_) => "neg".to_string(),
+            Expr::Abs(_) => "abs".to_string(),
+            Expr::Bin(op, ..) => op.to_string(),
+        };
+        out.push_str(&format!("    n{} [label=\"{}\"];\n", id, label));
+        let children: Vec<&Expr> = match expr {
+            Expr::Num(_) => Vec::new(),
+            Expr::Neg(inner) | Expr::Abs(inner) => vec![inner],
+            Expr::Bin(_, lhs, rhs) => vec![lhs, rhs],
+        };
+        for child in children {
+            let child_id = walk(child, next, out);
+            out.push_str(&format!("    n{} -> n{};\n", id, child_id));
+        }
+        id
+    }
+
+    let mut out = String::from("digraph expr {\n");
+    walk(expr, &mut 0, &mut out);
+    out.push_str("}\n");
+    out
+}
+
+/// 同级运算符（`+ -` 或 `* /`）连写且没有括号时的分组方式。
+///
+/// 标准算术是左结合：`8 - 3 - 3` 即 `(8 - 3) - 3`；某些变体玩法按右结合理解为
+/// `8 - (3 - 3)`。只影响没有括号的同级连写，乘除仍优先于加减。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Associativity {
+    #[default]
+    Left,
+    Right,
+}
+
+// Precedence tier of a binary operator; 0 for operators the parser doesn't know.
+fn tier(op: char) -> u8 {
+    match op {
+        '+' | '-' => 1,
+        '*' | '/' => 2,
+        _ => 0,
+    }
+}
+
+// Operators sharing a tier group with each other under `Associativity`.
+fn same_tier(a: char, b: char) -> bool {
+    tier(a) != 0 && tier(a) == tier(b)
+}
+
+/// 用尽量少的括号渲染表达式，例如 `(6 * 2) + (3 * 4)` 写作 `6 * 2 + 3 * 4`。
+///
+/// 按标准规则（乘除优先、同级左结合）省略不影响结构的括号：优先级更高的子运算、
+/// 以及同级运算的左操作数都不加括号。结果用 `parse_expr` 解析后得到同一棵树。
+pub fn display_minimal(expr: &Expr) -> String {
+    match expr {
+        Expr::Num(v) => v.to_string(),
+        Expr::Neg(inner) => match **inner {
+            Expr::Bin(..) => format!("-({})", display_minimal(inner)),
+            _ => format!("-{}", display_minimal(inner)),
+        },
+        Expr::Abs(inner) => format!("|{}|", display_minimal(inner)),
+        Expr::Bin(op, lhs, rhs) => {
+            let operand = |child: &Expr, is_left: bool| match child {
+                Expr::Bin(c, ..) if tier(*c) > tier(*op) || (is_left && same_tier(*c, *op)) => {
+                    display_minimal(child)
+                }
+                Expr::Bin(..) => format!("({})", display_minimal(child)),
+                _ => display_minimal(child),
+            };
+            format!("{} {} {}", operand(lhs, true), op, operand(rhs, false))
+        }
+    }
+}
+
+/// 按指定的结合方式渲染表达式，省略该结合方式下多余的括号。
+///
+/// 与 `Display` 相比，只有与父节点同级、且位于结合一侧的子运算不加括号：
+/// 左结合时 `(8 - 3) - 3` 写作 `8 - 3 - 3`，右结合时 `8 - (3 - 3)` 写作
+/// `8 - 3 - 3`。用 `parse_expr_with` 以同样的结合方式解析，得到原来的树。
+pub fn display_with(expr: &Expr, assoc: Associativity) -> String {
+    let operand = |child: &Expr, implicit: bool| match child {
+        Expr::Bin(..) if implicit => display_with(child, assoc),
+        Expr::Bin(..) => format!("({})", display_with(child, assoc)),
+        _ => display_with(child, assoc),
+    };
+    match expr {
+        Expr::Num(v) => v.to_string(),
+        Expr::Neg(inner) => format!("-{}", operand(inner, false)),
+        Expr::Abs(inner) => format!("|{}|", display_with(inner, assoc)),
+        Expr::Bin(op, lhs, rhs) => {
+            let grouped = |child: &Expr| matches!(child, Expr::Bin(c, ..) if same_tier(*c, *op));
+            format!(
+                "{} {} {}",
+                operand(lhs, assoc == Associativity::Left && grouped(lhs)),
+                op,
+                operand(rhs, assoc == Associativity::Right && grouped(rhs))
+            )
+        }
+    }
+}
+
+/// 按 `Display` 的括号方式渲染表达式，但在无歧义处省略乘号，例如
+/// `6 * (2 + 2)` 写作 `6(2 + 2)`、`(1 + 2) * (3 + 5)` 写作 `(1 + 2)(3 + 5)`。
+///
+/// 只有右操作数是带括号的子运算、且左操作数是数字或带括号的子运算时才省略；
+/// 两个数字相乘仍写作 `6 * 2`（直接相连会变成 `62`），左操作数带一元负号时
+/// 也保留乘号，避免 `-(1 + 2)(3 + 5)` 中负号的作用范围不清。
+/// 因为所有子运算都带括号，省略乘号不会与 `8 / 2(2 + 2)` 这类写法混淆。
+pub fn display_implicit_mul(expr: &Expr) -> String {
+    let operand = |child: &Expr| match child {
+        Expr::Bin(..) => format!("({})", display_implicit_mul(child)),
+        _ => display_implicit_mul(child),
+    };
+    match expr {
+        Expr::Num(v) => v.to_string(),
+        Expr::Neg(inner) => format!("-{}", operand(inner)),
+        Expr::Abs(inner) => format!("|{}|", display_implicit_mul(inner)),
+        Expr::Bin('*', lhs, rhs)
+            if matches!(**rhs, Expr::Bin(..)) && !matches!(**lhs, Expr::Neg(_)) =>
+        {
+            format!("{}{}", operand(lhs), operand(rhs))
+        }
+        Expr::Bin(op, lhs, rhs) => format!("{} {} {}", operand(lhs), op, operand(rhs)),
+    }
+}
+
+/// 把表达式渲染为 LaTeX 数学公式，例如 `8 / (3 - (8 / 3))` 得到
+/// `\frac{8}{3 - \frac{8}{3}}`。
+///
+/// 除法写作 `\frac{}{}`，分子分母自带分组，因此分数本身以及分数内部都不需要括号；
+/// 乘法写作 `\times`。其余括号与 `display_minimal` 的规则相同，写作
+/// `\left( ... \right)`，使括号能随内部分数的高度伸缩。结果不含 `$` 定界符。
+pub fn to_latex(expr: &Expr) -> String {
+    match expr {
+        Expr::Num(v) => v.to_string(),
+        Expr::Neg(inner) => match **inner {
+            Expr::Bin(op, ..) if op != '/' => format!("-\\left({}\\right)", to_latex(inner)),
+            _ => format!("-{}", to_latex(inner)),
+        },
+        Expr::Abs(inner) => format!("\\left|{}\\right|", to_latex(inner)),
+        Expr::Bin('/', lhs, rhs) => format!("\\frac{{{}}}{{{}}}", to_latex(lhs), to_latex(rhs)),
+        Expr::Bin(op, lhs, rhs) => {
+            let operand = |child: &Expr, is_left: bool| match child {
+                Expr::Bin(c, ..)
+                    if *c != '/' && tier(*c) <= tier(*op) && !(is_left && same_tier(*c, *op)) =>
+                {
+                    format!("\\left({}\\right)", to_latex(child))
+                }
+                _ => to_latex(child),
+            };
+            let symbol = match op {
+                '*' => "\\times".to_string(),
+                other => other.to_string(),
+            };
+            format!("{} {} {}", operand(lhs, true), symbol, operand(rhs, false))
+        }
+    }
+}
+
+/// 解析失败时的错误信息，`position` 为出错处的字符下标（从 0 开始）。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 把用户输入的算式解析为 `Expr`。
+///
+/// 支持的语法：
+/// - 非负整数，以及一元负号（`-3`、`-(1 + 2)`）；
+/// - `+ - * /` 四种二元运算，乘除优先于加减，同级运算左结合；
+/// - 圆括号与任意空白。
+///
+/// 绝对值竖线不属于标准玩法，这里会当作非法字符拒绝；需要时改用 `parse_expr_abs`。
+/// 一元负号直接作用在数字上时会折叠成负数叶子，例如 `-3` 解析为 `Num(-3.0)`。
+/// 出错时返回的 `ParseError` 会指出出错的字符位置。
+///
+/// 示例：
+/// ```rust
+/// use twelve_four_puzzle::expr::{eval, parse_expr};
+///
+/// let expr = parse_expr("-3 + 4 * 2").unwrap();
+/// assert_eq!(eval(&expr), Some(5.0));
+/// ```
+pub fn parse_expr(s: &str) -> Result<Expr, ParseError> {
+    parse_expr_with(s, Associativity::Left)
+}
+
+/// 与 `parse_expr` 相同，但同级运算符按 `assoc` 分组。
+///
+/// ```rust
+/// use twelve_four_puzzle::expr::{eval, parse_expr_with, Associativity};
+///
+/// assert_eq!(eval(&parse_expr_with("8 - 3 - 3", Associativity::Left).unwrap()), Some(2.0));
+/// assert_eq!(eval(&parse_expr_with("8 - 3 - 3", Associativity::Right).unwrap()), Some(8.0));
+/// ```
+pub fn parse_expr_with(s: &str, assoc: Associativity) -> Result<Expr, ParseError> {
+    parse(s, assoc, false)
+}
+
+/// 与 `parse_expr` 相同，但额外接受表示绝对值的竖线（`|3 - 8|`），
+/// 用于读回 `variants::solve_with_abs` 这类变体玩法给出的算式。
+///
+/// ```rust
+/// use twelve_four_puzzle::expr::{eval, parse_expr, parse_expr_abs};
+///
+/// assert_eq!(eval(&parse_expr_abs("|3 - 8| * 4").unwrap()), Some(20.0));
+/// assert!(parse_expr("|3 - 8| * 4").is_err());
+/// ```
+pub fn parse_expr_abs(s: &str) -> Result<Expr, ParseError> {
+    parse(s, Associativity::Left, true)
+}
+
+fn parse(s: &str, assoc: Associativity, allow_abs: bool) -> Result<Expr, ParseError> {
+    let mut parser = Parser {
+        chars: s.chars().collect(),
+        pos: 0,
+        assoc,
+        allow_abs,
+    };
+    let expr = parser.parse_sum()?;
+    parser.skip_whitespace();
+    match parser.peek() {
+        None => Ok(expr),
+        Some(')') => Err(parser.error("unmatched ')'")),
+        Some(c) => Err(parser.error(&format!("unexpected character '{}'", c))),
+    }
+}
+
+// A small recursive-descent parser; each `parse_*` level handles one
+// precedence tier and collects its chain, which `group` folds by `assoc`.
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    assoc: Associativity,
+    allow_abs: bool,
+}
+
+impl Parser {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            position: self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    // Fold `first op1 x1 op2 x2 ...` into a tree according to `assoc`.
+    fn group(&self, first: Expr, rest: Vec<(char, Expr)>) -> Expr {
+        match self.assoc {
+            Associativity::Left => rest
+                .into_iter()
+                .fold(first, |lhs, (op, rhs)| Expr::bin(op, lhs, rhs)),
+            Associativity::Right => {
+                let mut operands = vec![first];
+                let mut ops = Vec::new();
+                for (op, operand) in rest {
+                    ops.push(op);
+                    operands.push(operand);
+                }
+                let mut rhs = operands.pop().expect("chain has an operand");
+                while let (Some(op), Some(lhs)) = (ops.pop(), operands.pop()) {
+                    rhs = Expr::bin(op, lhs, rhs);
+                }
+                rhs
+            }
+        }
+    }
+
+    fn parse_sum(&mut self) -> Result<Expr, ParseError> {
+        let first = self.parse_product()?;
+        let mut rest = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(op @ ('+' | '-')) => {
+                    self.pos += 1;
+                    rest.push((op, self.parse_product()?));
+                }
+                _ => return Ok(self.group(first, rest)),
+            }
+        }
+    }
+
+    fn parse_product(&mut self) -> Result<Expr, ParseError> {
+        let first = self.parse_unary()?;
+        let mut rest = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(op @ ('*' | '/')) => {
+                    self.pos += 1;
+                    rest.push((op, self.parse_unary()?));
+                }
+                _ => return Ok(self.group(first, rest)),
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        self.skip_whitespace();
+        if self.peek() == Some('-') {
+            self.pos += 1;
+            return Ok(match self.parse_unary()? {
+                Expr::Num(v) => Expr::Num(-v),
+                inner => Expr::Neg(Box::new(inner)),
+            });
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('(') => {
+                let open = self.pos;
+                self.pos += 1;
+                let inner = self.parse_sum()?;
+                self.skip_whitespace();
+                if self.peek() == Some(')') {
+                    self.pos += 1;
+                    Ok(inner)
+                } else {
+                    Err(ParseError {
+                        position: open,
+                        message: "unclosed '('".to_string(),
+                    })
+                }
+            }
+            Some('|') if self.allow_abs => {
+                let open = self.pos;
+                self.pos += 1;
+                let inner = self.parse_sum()?;
+                self.skip_whitespace();
+                if self.peek() == Some('|') {
+                    self.pos += 1;
+                    Ok(Expr::Abs(Box::new(inner)))
+                } else {
+                    Err(ParseError {
+                        position: open,
+                        message: "unclosed '|'".to_string(),
+                    })
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+                let digits: String = self.chars[start..self.pos].iter().collect();
+                digits
+                    .parse::<f64>()
+                    .map(Expr::Num)
+                    .map_err(|_| ParseError {
+                        position: start,
+                        message: format!("invalid number '{}'", digits),
+                    })
+            }
+            Some(c) => Err(self.error(&format!("unexpected character '{}'", c))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_precedence_and_unary_minus() {
+        let expr = parse_expr("-3+4*2").unwrap();
+        assert_eq!(
+            expr,
+            Expr::bin(
+                '+',
+                Expr::Num(-3.0),
+                Expr::bin('*', Expr::Num(4.0), Expr::Num(2.0))
+            )
+        );
+        assert_eq!(eval(&expr), Some(5.0));
+        assert_eq!(eval(&parse_expr("-(1 + 2) * 4").unwrap()), Some(-12.0));
+        assert_eq!(eval(&parse_expr("2 - -3").unwrap()), Some(5.0));
+    }
+
+    #[test]
+    fn test_parse_left_associativity() {
+        assert_eq!(eval(&parse_expr("8 - 3 - 3").unwrap()), Some(2.0));
+        assert_eq!(eval(&parse_expr("8 / 4 / 2").unwrap()), Some(1.0));
+    }
+
+    #[test]
+    fn test_parse_whitespace_and_display_round_trip() {
+        let expr = parse_expr("  ( 6*2 ) +\t(3 * 4) ").unwrap();
+        assert_eq!(expr.to_string(), "(6 * 2) + (3 * 4)");
+        assert_eq!(parse_expr(&expr.to_string()).unwrap(), expr);
+        assert_eq!(eval(&expr), Some(24.0));
+    }
+
+    #[test]
+    fn test_parse_errors_report_position() {
+        let err = parse_expr("(1 + 2").unwrap_err();
+        assert_eq!(err.position, 0);
+        assert_eq!(err.message, "unclosed '('");
+
+        let err = parse_expr("1 + 2)").unwrap_err();
+        assert_eq!(err.position, 5);
+
+        let err = parse_expr("1 + ").unwrap_err();
+        assert_eq!(err.position, 4);
+
+        let err = parse_expr("3 $ 4").unwrap_err();
+        assert_eq!(err.position, 2);
+
+        assert!(parse_expr("").is_err());
+        assert!(parse_expr("()").is_err());
+    }
+
+    #[test]
+    fn test_depth_and_operators() {
+        let balanced = parse_expr("(6 * 2) + (3 * 4)").unwrap();
+        assert_eq!(balanced.depth(), 2);
+        assert_eq!(balanced.operators(), vec!['*', '+', '*']);
+
+        let chained = parse_expr("((2 * 3) * 4) / 1").unwrap();
+        assert_eq!(chained.depth(), 3);
+        assert_eq!(chained.operators(), vec!['*', '*', '/']);
+        assert_eq!(Expr::Num(5.0).depth(), 0);
+    }
+
+    #[test]
+    fn test_canonical_collapses_commutative_forms() {
+        let a = parse_expr("(1 + 2) * 8").unwrap();
+        let b = parse_expr("8 * (2 + 1)").unwrap();
+        assert_eq!(canonical(&a), canonical(&b));
+        // Subtraction and division are not commutative.
+        let c = parse_expr("8 - 2").unwrap();
+        let d = parse_expr("2 - 8").unwrap();
+        assert_ne!(canonical(&c), canonical(&d));
+    }
+
+    #[test]
+    fn test_to_rpn() {
+        let expr = parse_expr("(6 - 3) * (2 + 6)").unwrap();
+        assert_eq!(to_rpn(&expr), "6 3 - 2 6 + *");
+        assert_eq!(to_rpn(&parse_expr("-(1 + 2)").unwrap()), "1 2 + neg");
+    }
+
+    #[test]
+    fn test_eval_division_by_zero() {
+        assert_eq!(eval(&parse_expr("1 / (2 - 2)").unwrap()), None);
+    }
+
+    #[test]
+    fn test_abs() {
+        let expr = parse_expr_abs("|3 - 8| * (2 + |1 - 3|)").unwrap();
+        assert_eq!(eval(&expr), Some(20.0));
+        assert_eq!(expr.to_string(), "|3 - 8| * (2 + |1 - 3|)");
+        assert_eq!(
+            parse_expr_abs("||8 - 3| - 10|").unwrap().to_string(),
+            "||8 - 3| - 10|"
+        );
+        assert_eq!(to_rpn(&parse_expr_abs("|3 - 8|").unwrap()), "3 8 - abs");
+        let joined: String = tokenize_solution(&expr)
+            .iter()
+            .map(Token::to_string)
+            .collect();
+        assert_eq!(joined, expr.to_string());
+        assert_eq!(
+            parse_expr_abs("|3 - 8").unwrap_err().message,
+            "unclosed '|'"
+        );
+
+        // The default parser keeps to the four standard operators.
+        let err = parse_expr("|3 - 8| * 4").unwrap_err();
+        assert_eq!(err.position, 0);
+        assert_eq!(err.message, "unexpected character '|'");
+    }
+
+    #[test]
+    fn test_tokenize_solution() {
+        let expr = parse_expr("8 / (3 - 8 / 3)").unwrap();
+        let tokens = tokenize_solution(&expr);
+        assert_eq!(
+            tokens[..3],
+            [Token::Num(8.0), Token::Op('/'), Token::LParen]
+        );
+        assert_eq!(tokens.last(), Some(&Token::RParen));
+        for input in ["8 / (3 - 8 / 3)", "(6 * 2) + (3 * 4)", "-(1 + 2) * 4", "7"] {
+            let expr = parse_expr(input).unwrap();
+            let joined: String = tokenize_solution(&expr)
+                .iter()
+                .map(Token::to_string)
+                .collect();
+            assert_eq!(joined, expr.to_string());
+        }
+    }
+
+    #[test]
+    fn test_display_implicit_mul() {
+        let show = |s: &str| display_implicit_mul(&parse_expr(s).unwrap());
+        assert_eq!(show("6 * (2 + 2)"), "6(2 + 2)");
+        assert_eq!(show("6 * 2"), "6 * 2");
+        assert_eq!(show("(1 + 2) * (3 + 5)"), "(1 + 2)(3 + 5)");
+        assert_eq!(show("(2 + 2) * 6"), "(2 + 2) * 6");
+        assert_eq!(show("8 / (2 * (2 + 2))"), "8 / (2(2 + 2))");
+        assert_eq!(show("-(1 + 2) * (3 + 5)"), "-(1 + 2) * (3 + 5)");
+    }
+
+    #[test]
+    fn test_to_latex() {
+        let latex = |s: &str| to_latex(&parse_expr(s).unwrap());
+        assert_eq!(latex("8 / (3 - (8 / 3))"), r"\frac{8}{3 - \frac{8}{3}}");
+        assert_eq!(latex("(6 * 2) + (3 * 4)"), r"6 \times 2 + 3 \times 4");
+        assert_eq!(latex("(1 + 2) * 8"), r"\left(1 + 2\right) \times 8");
+        assert_eq!(latex("12 - (8 / 2)"), r"12 - \frac{8}{2}");
+        assert_eq!(latex("6 - (4 - 2)"), r"6 - \left(4 - 2\right)");
+        assert_eq!(latex("-(1 + 2)"), r"-\left(1 + 2\right)");
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = to_dot(&parse_expr("8 / (3 - (8 / 3))").unwrap());
+        assert!(dot.starts_with("digraph expr {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot.matches("[label=").count(), 7);
+        assert_eq!(dot.matches(" -> ").count(), 6);
+        assert!(dot.contains("    n0 [label=\"/\"];\n"));
+        assert!(dot.contains("    n0 -> n1;\n"));
+        assert!(dot.contains("    n0 -> n2;\n"));
+        // Every edge refers to declared nodes.
+        for line in dot.lines().filter(|l| l.contains(" -> ")) {
+            for node in line.trim().trim_end_matches(';').split(" -> ") {
+                assert!(dot.contains(&format!("    {} [label=", node)), "{}", line);
+            }
+        }
+    }
+
+    #[test]
+    fn test_display_minimal() {
+        for (input, expected) in [
+            ("(6 * 2) + (3 * 4)", "6 * 2 + 3 * 4"),
+            ("8 / (3 - (8 / 3))", "8 / (3 - 8 / 3)"),
+            ("((1 + 2) - 3) * 4", "(1 + 2 - 3) * 4"),
+            ("1 - (2 - 3)", "1 - (2 - 3)"),
+            ("-(1 + 2) * 4", "-(1 + 2) * 4"),
+        ] {
+            let expr = parse_expr(input).unwrap();
+            let minimal = display_minimal(&expr);
+            assert_eq!(minimal, expected);
+            assert_eq!(parse_expr(&minimal).unwrap(), expr);
+        }
+    }
+
+    #[test]
+    fn test_associativity() {
+        let left = parse_expr_with("8 - 3 - 3", Associativity::Left).unwrap();
+        let right = parse_expr_with("8 - 3 - 3", Associativity::Right).unwrap();
+        assert_eq!(eval(&left), Some(2.0));
+        assert_eq!(eval(&right), Some(8.0));
+        assert_eq!(left.to_string(), "(8 - 3) - 3");
+        assert_eq!(right.to_string(), "8 - (3 - 3)");
+        assert_eq!(
+            eval(&parse_expr_with("24 / 2 / 3 + 1", Associativity::Right).unwrap()),
+            Some(37.0)
+        );
+
+        assert_eq!(display_with(&left, Associativity::Left), "8 - 3 - 3");
+        assert_eq!(display_with(&left, Associativity::Right), "(8 - 3) - 3");
+        assert_eq!(display_with(&right, Associativity::Right), "8 - 3 - 3");
+        // Different tiers always keep their parentheses.
+        let mixed = parse_expr("(1 + 2) * 8").unwrap();
+        assert_eq!(display_with(&mixed, Associativity::Left), "(1 + 2) * 8");
+    }
+}
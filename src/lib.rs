@@ -0,0 +1,889 @@
+//! # 程序说明
+//!
+//! 这是一个 24 点求解器：给定 4 张扑克牌（数值 1~13），
+//! 使用加减乘除与所有括号组合来寻找得到 24 的表达式。
+//! 命令行程序（`src/main.rs`）负责随机抽牌并把 "有解" 或 "无解" 的结果
+//! 写入 `log/24_game_log.txt` 日志，求解逻辑则都放在本库中，便于复用和测试。
+//!
+//! ## 算法完整性与正确性
+//! - **完整性**：对 4 张牌进行全排列，共 4! = 24 种顺序；
+//!   每一顺序都会尝试 3 个运算符位的所有 4^3 组合；
+//!   同时覆盖五种合法的二叉树括号形态，等价于枚举所有四元表达式结构。
+//!   因此任何合法的 24 点表达式必定会被枚举到；`tests/completeness.rs`
+//!   用独立的暴力枚举与精确求值核对了这一点。
+//! - **正确性**：所有运算在 `f64` 中完成，并使用 `EPSILON` 进行浮点比较；
+//!   除法在分母绝对值小于 `EPSILON` 时会被忽略以避免除以零。
+//!   这些约束确保枚举到的表达式都是真实可计算且确实等于 24 的结果。
+
+pub mod analysis;
+pub mod answer;
+pub mod cache;
+pub mod card;
+pub mod config;
+pub mod decimal;
+pub mod draw;
+pub mod explain;
+pub mod expr;
+pub mod logging;
+pub mod rational;
+pub mod reach;
+pub mod solution;
+pub mod stats;
+pub mod survey;
+pub mod variants;
+pub mod worksheet;
+
+use config::{DivisionMode, SolverConfig};
+use decimal::Decimal;
+use expr::Expr;
+use rational::Rational;
+use solution::Solution;
+use std::collections::{HashMap, HashSet};
+
+/// 目标值：四张牌需要凑出的结果。
+pub const TARGET: f64 = 24.0;
+/// 浮点比较与除数判零所用的容差。
+pub const EPSILON: f64 = 1e-6;
+/// 标准规则下允许使用的四种运算符。
+pub const OPERATORS: [char; 4] = ['+', '-', '*', '/'];
+
+/// 对给定的 4 张牌，返回所有可得到 24 的表达式。
+///
+/// 为了确保覆盖所有组合，先将牌转为 `f64` 并生成全排列，
+/// 再对每一个排列调用 `find_solutions_for_permutation` 来遍历
+/// 运算符与括号结构。按表达式字符串去重，避免重复表达式。
+///
+/// 由于枚举了全部排列，解集只取决于这四张牌构成的多重集合，与传入的顺序无关
+/// （返回的 `Vec` 顺序不作保证）；`survey` 模块的穷举正是依赖这一点。
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(cards = ?cards))
+)]
+pub fn solve_24(cards: &[i32]) -> Vec<String> {
+    solve_with_ops(cards, &OPERATORS)
+}
+
+/// 与 `solve_24` 相同，但只允许使用 `ops` 中列出的运算符。
+///
+/// 例如 `solve_with_ops(cards, &['+', '*'])` 只寻找加法与乘法的解；
+/// `ops` 中不认识的运算符会被 `apply_op` 视为无效而自然跳过。
+pub fn solve_with_ops(cards: &[i32], ops: &[char]) -> Vec<String> {
+    let config = SolverConfig {
+        ops: ops.to_vec(),
+        ..SolverConfig::default()
+    };
+    solve_with_config(cards, &config)
+}
+
+/// 按 `config` 指定的目标值、容差、比较方式和运算符求解 4 张牌。
+///
+/// 这是所有 4 张牌求解函数共用的入口，`solve_24` 即默认配置下的结果。
+pub fn solve_with_config(cards: &[i32], config: &SolverConfig) -> Vec<String> {
+    solve_with_config_full(cards, config)
+        .into_iter()
+        .map(|solution| solution.text)
+        .collect()
+}
+
+/// 与 `solve_with_config` 相同，但返回结构化的 `Solution`。
+///
+/// 表达式树直接取自枚举时构造的树，不需要把字符串再解析一遍；
+/// 按字符串去重，结果与 `solve_with_config` 一一对应。
+pub fn solve_with_config_full(cards: &[i32], config: &SolverConfig) -> Vec<Solution> {
+    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
+
+    let mut all_solutions = HashMap::new();
+    for perm in permutations(&nums) {
+        let sols = find_solutions_for_permutation(&perm, config);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(?perm, solutions = sols.len(), "permutation searched");
+        all_solutions.extend(sols);
+    }
+    all_solutions
+        .into_values()
+        .map(Solution::from_expr)
+        .collect()
+}
+
+/// 按排列分组列出解，便于调试枚举过程。
+///
+/// 按 `permutations` 的枚举顺序，列出每个至少产生一个解的排列及其解（按字典序）。
+/// 牌中有重复点数时，相同的排列会按枚举时的实际次数重复出现。
+/// 所有分组中的解合并去重后，与 `solve_24` 的结果相同。
+pub fn solutions_by_permutation(cards: &[i32]) -> Vec<(Vec<f64>, Vec<String>)> {
+    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
+    let config = SolverConfig::default();
+    permutations(&nums)
+        .into_iter()
+        .filter_map(|perm| {
+            let mut sols: Vec<String> = find_solutions_for_permutation(&perm, &config)
+                .into_keys()
+                .collect();
+            sols.sort();
+            (!sols.is_empty()).then_some((perm, sols))
+        })
+        .collect()
+}
+
+/// 不使用除法求解，等价于 `solve_with_ops(cards, &['+', '-', '*'])`。
+pub fn solve_no_division(cards: &[i32]) -> Vec<String> {
+    solve_with_ops(cards, &['+', '-', '*'])
+}
+
+/// 判断一手牌是否有解，找到第一个解就立即返回。
+///
+/// 只需要知道"有没有解"时比 `solve_24` 快得多，因为不会格式化或收集表达式。
+pub fn is_solvable(cards: &[i32]) -> bool {
+    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
+    let triples = op_triples(&OPERATORS);
+    permutations(&nums).iter().any(|perm| {
+        triples.iter().any(|&ops| {
+            TreeShape::ALL.iter().any(|&shape| {
+                eval_struct(shape, perm, ops).is_some_and(|v| (v - TARGET).abs() < EPSILON)
+            })
+        })
+    })
+}
+
+/// 用任意张数的牌求 24，每张牌恰好使用一次。
+///
+/// `solve_24` 只处理 4 张牌的五种固定括号形态；这里对每个排列递归地枚举
+/// 所有二叉树形态（n 张牌共有卡特兰数 C(n-1) 种），因此也能处理 2、3、5 张牌。
+/// 对 4 张牌而言，结果与 `solve_24` 完全相同。
+/// 张数增加时搜索空间按 n! * C(n-1) * 4^(n-1) 增长，不适合用于很多张牌。
+pub fn solve_subset(cards: &[i32]) -> Vec<String> {
+    solve_subset_full(cards)
+        .into_iter()
+        .map(|solution| solution.text)
+        .collect()
+}
+
+/// 与 `solve_subset` 相同，但返回结构化的 `Solution`，表达式树直接取自枚举结果。
+pub fn solve_subset_full(cards: &[i32]) -> Vec<Solution> {
+    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
+
+    let mut all_solutions = HashMap::new();
+    for perm in permutations(&nums) {
+        for (value, expr) in trees_for_sequence(&perm, &OPERATORS) {
+            if (value - TARGET).abs() < EPSILON {
+                all_solutions.entry(expr.to_string()).or_insert(expr);
+            }
+        }
+    }
+    all_solutions
+        .into_values()
+        .map(Solution::from_expr)
+        .collect()
+}
+
+/// 枚举固定顺序的 `nums` 上所有可计算的表达式树及其值。
+///
+/// 在每个分割点把序列切成左右两段，分别递归枚举后再用 `ops` 组合；
+/// 除数接近 0 的组合会被丢弃。空序列返回空列表。
+pub(crate) fn trees_for_sequence(nums: &[f64], ops: &[char]) -> Vec<(f64, Expr)> {
+    let atoms: Vec<(f64, Expr)> = nums.iter().map(|&v| (v, Expr::Num(v))).collect();
+    trees_for_atoms(&atoms, ops)
+}
+
+/// 与 `trees_for_sequence` 相同，但每个操作数是一棵已知值的子树而不一定是数字，
+/// 用于把已经固定下来的部分表达式当作一个整体参与枚举。
+pub(crate) fn trees_for_atoms(atoms: &[(f64, Expr)], ops: &[char]) -> Vec<(f64, Expr)> {
+    if atoms.len() == 1 {
+        return atoms.to_vec();
+    }
+
+    let mut trees = Vec::new();
+    for split in 1..atoms.len() {
+        let left = trees_for_atoms(&atoms[..split], ops);
+        let right = trees_for_atoms(&atoms[split..], ops);
+        for (lv, lexpr) in &left {
+            for (rv, rexpr) in &right {
+                for &op in ops {
+                    if let Some(value) = apply_op(*lv, *rv, op) {
+                        trees.push((value, Expr::bin(op, lexpr.clone(), rexpr.clone())));
+                    }
+                }
+            }
+        }
+    }
+    trees
+}
+
+/// 返回给定 4 张牌的解的个数（即 `solve_24` 去重后的表达式数量）。
+///
+/// 无解时返回 0，因此也可以用来判断一手牌是否有解。
+pub fn count_solutions(cards: &[i32]) -> usize {
+    solve_24(cards).len()
+}
+
+/// 返回 `nums` 的所有排列（每个排列为 `Vec<f64>`）。
+///
+/// 详细说明：
+/// - 该函数以递归方式实现。对于非空输入，函数会枚举每个位置 `i` 作为当前头元素 `v`，
+///   构造剩余元素 `rest`（去掉索引 `i` 的元素），递归计算 `rest` 的所有排列，
+///   然后把 `v` 置于每个子排列的头部，得到完整排列列表。
+/// - 基准情形：当 `nums` 为空时，返回 `vec![vec![]]`，即包含一个空排列，这样递归拼接时能正确回溯。
+/// - 风格与性能：该实现是函数式的——不依赖外部可变状态或回调，返回新分配的数据结构，
+///   因而易于理解与测试。其时间复杂度为 O(n! * n)，空间复杂度也为 O(n!)（因为要保存所有排列），
+///   对本程序的 n=4 情形而言开销可忽略。
+///
+/// 示例：
+/// ```rust
+/// use twelve_four_puzzle::permutations;
+///
+/// let perms = permutations(&[1.0, 2.0, 3.0]);
+/// assert_eq!(perms.len(), 6);
+/// // `perms` 将包含 6 个排列：
+/// // [1.0, 2.0, 3.0]
+/// // [1.0, 3.0, 2.0]
+/// // [2.0, 1.0, 3.0]
+/// // [2.0, 3.0, 1.0]
+/// // [3.0, 1.0, 2.0]
+/// // [3.0, 2.0, 1.0]
+/// ```
+pub fn permutations(nums: &[f64]) -> Vec<Vec<f64>> {
+    if nums.is_empty() {
+        return vec![vec![]];
+    }
+
+    let mut result = Vec::new();
+    for (i, &v) in nums.iter().enumerate() {
+        let mut rest = nums.to_vec();
+        rest.remove(i);
+        for mut perm in permutations(&rest) {
+            perm.insert(0, v);
+            result.push(perm);
+        }
+    }
+
+    result
+}
+
+/// 四个操作数的五种括号形态，对应所有不同的二叉树结构。
+///
+/// 运算符 `op1`、`op2`、`op3` 总是按它们在表达式中从左到右出现的顺序编号。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TreeShape {
+    /// `(a op1 b) op2 (c op3 d)`
+    Balanced,
+    /// `((a op1 b) op2 c) op3 d`
+    LeftChain,
+    /// `a op1 (b op2 (c op3 d))`
+    RightChain,
+    /// `(a op1 (b op2 c)) op3 d`
+    LeftInner,
+    /// `a op1 ((b op2 c) op3 d)`
+    RightInner,
+}
+
+impl TreeShape {
+    /// 全部五种形态，顺序与求解器的枚举顺序一致。
+    pub const ALL: [TreeShape; 5] = [
+        TreeShape::Balanced,
+        TreeShape::LeftChain,
+        TreeShape::RightChain,
+        TreeShape::LeftInner,
+        TreeShape::RightInner,
+    ];
+}
+
+/// 枚举 `n` 个叶子的全部二叉树形态并返回不同形态的个数，即卡特兰数 C(n-1)。
+///
+/// 不套用公式，而是把每种形态实际构造成括号串（如 `((xx)x)`）后去重计数，
+/// 用来程序化地核对完整性论证：`n = 4` 时恰好是 `TreeShape` 的 5 种形态。
+/// `n = 0` 时返回 0。
+pub fn enumerate_tree_shapes(n: usize) -> usize {
+    fn shapes(n: usize) -> Vec<String> {
+        if n == 1 {
+            return vec!["x".to_string()];
+        }
+        let mut all = Vec::new();
+        for split in 1..n {
+            for left in shapes(split) {
+                for right in shapes(n - split) {
+                    all.push(format!("({}{})", left, right));
+                }
+            }
+        }
+        all
+    }
+
+    if n == 0 {
+        return 0;
+    }
+    shapes(n).into_iter().collect::<HashSet<_>>().len()
+}
+
+/// 按 `shape` 的计算顺序对固定顺序的 4 个数求值。
+///
+/// 每一步都调用 `apply_op`，任何一步无效（除数接近 0）时整体返回 `None`。
+pub fn eval_struct(shape: TreeShape, perm: &[f64], ops: [char; 3]) -> Option<f64> {
+    eval_shape(shape, [perm[0], perm[1], perm[2], perm[3]], ops, apply_op)
+}
+
+/// 与 `eval_struct` 相同，但全程使用 `i64` 精确计算（见 `apply_op_i64`）。
+///
+/// 溢出或除不尽时返回 `None`，用于"中间结果必须是整数"的模式。
+pub fn eval_struct_i64(shape: TreeShape, perm: &[f64], ops: [char; 3]) -> Option<i64> {
+    let [a, b, c, d] = [perm[0], perm[1], perm[2], perm[3]].map(|v| v as i64);
+    eval_shape(shape, [a, b, c, d], ops, apply_op_i64)
+}
+
+/// 与 `eval_struct` 相同，但用 `Rational` 做精确的有理数计算。
+///
+/// 除以 0 或 `i64` 溢出时返回 `None`；`perm` 中的数必须是整数。
+pub fn eval_struct_exact(shape: TreeShape, perm: &[f64], ops: [char; 3]) -> Option<Rational> {
+    let [a, b, c, d] = [perm[0], perm[1], perm[2], perm[3]].map(|v| Rational::from_int(v as i64));
+    eval_shape(shape, [a, b, c, d], ops, Rational::apply)
+}
+
+/// 与 `eval_struct` 相同，但用定点小数 `Decimal` 计算（见 `decimal` 模块）。
+pub fn eval_struct_decimal(shape: TreeShape, perm: &[f64], ops: [char; 3]) -> Option<Decimal> {
+    let [a, b, c, d] = [perm[0], perm[1], perm[2], perm[3]].map(|v| Decimal::from_int(v as i64));
+    eval_shape(shape, [a, b, c, d], ops, Decimal::apply)
+}
+
+// Shared driver for the alternative arithmetic backends: every permutation,
+// operator triple and shape is evaluated with `eval`, and the expression
+// strings whose value satisfies `is_target` are collected without duplicates.
+pub(crate) fn solve_by<T>(
+    cards: &[i32],
+    eval: impl Fn(TreeShape, &[f64], [char; 3]) -> Option<T>,
+    is_target: impl Fn(T) -> bool,
+) -> Vec<String> {
+    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
+    let mut solutions = HashSet::new();
+    for perm in permutations(&nums) {
+        for ops in op_triples(&OPERATORS) {
+            for shape in TreeShape::ALL {
+                if eval(shape, &perm, ops).is_some_and(&is_target) {
+                    solutions.insert(build_struct(shape, &perm, ops).to_string());
+                }
+            }
+        }
+    }
+    solutions.into_iter().collect()
+}
+
+// The five structures written once, generic over the number type and the
+// operator implementation so every arithmetic backend shares the same order.
+pub(crate) fn eval_shape<T: Copy>(
+    shape: TreeShape,
+    [a, b, c, d]: [T; 4],
+    [op1, op2, op3]: [char; 3],
+    apply: impl Fn(T, T, char) -> Option<T>,
+) -> Option<T> {
+    match shape {
+        TreeShape::Balanced => apply(apply(a, b, op1)?, apply(c, d, op3)?, op2),
+        TreeShape::LeftChain => apply(apply(apply(a, b, op1)?, c, op2)?, d, op3),
+        TreeShape::RightChain => apply(a, apply(b, apply(c, d, op3)?, op2)?, op1),
+        TreeShape::LeftInner => apply(apply(a, apply(b, c, op2)?, op1)?, d, op3),
+        TreeShape::RightInner => apply(a, apply(apply(b, c, op2)?, d, op3)?, op1),
+    }
+}
+
+/// 构造 `shape` 形态下固定顺序 4 个数的表达式树。
+pub fn build_struct(shape: TreeShape, perm: &[f64], ops: [char; 3]) -> Expr {
+    let [op1, op2, op3] = ops;
+    let [a, b, c, d] = [perm[0], perm[1], perm[2], perm[3]].map(Expr::Num);
+    match shape {
+        TreeShape::Balanced => Expr::bin(op2, Expr::bin(op1, a, b), Expr::bin(op3, c, d)),
+        TreeShape::LeftChain => Expr::bin(op3, Expr::bin(op2, Expr::bin(op1, a, b), c), d),
+        TreeShape::RightChain => Expr::bin(op1, a, Expr::bin(op2, b, Expr::bin(op3, c, d))),
+        TreeShape::LeftInner => Expr::bin(op3, Expr::bin(op1, a, Expr::bin(op2, b, c)), d),
+        TreeShape::RightInner => Expr::bin(op1, a, Expr::bin(op3, Expr::bin(op2, b, c), d)),
+    }
+}
+
+/// 列出从 `ops` 中选取三个运算符（可重复）的所有组合，共 `ops.len()^3` 种。
+pub fn op_triples(ops: &[char]) -> Vec<[char; 3]> {
+    let mut triples = Vec::new();
+    for &op1 in ops {
+        for &op2 in ops {
+            for &op3 in ops {
+                triples.push([op1, op2, op3]);
+            }
+        }
+    }
+    triples
+}
+
+/// 调试用：列出一个排列下全部 5 种形态 × 64 种运算符组合的计算结果。
+///
+/// 共 320 项，顺序为先运算符组合、后形态，与求解器的枚举顺序一致；
+/// 不论结果是否等于 24 都会列出，除数接近 0 的组合值为 `f64::NAN`。
+pub fn dump_permutation(perm: &[f64]) -> Vec<(TreeShape, [char; 3], f64)> {
+    let mut dump = Vec::new();
+    for ops in op_triples(&OPERATORS) {
+        for shape in TreeShape::ALL {
+            let value = eval_struct(shape, perm, ops).unwrap_or(f64::NAN);
+            dump.push((shape, ops, value));
+        }
+    }
+    dump
+}
+
+/// 统计一个排列的 5 × 64 个（形态, 运算符组合）中有多少个能得到 24。
+///
+/// 用来衡量一个排列有多"丰富"。按组合计数而不是按字符串去重，
+/// 因此数值相同的不同组合（如 `6 + 6` 写在不同位置）各算一次。
+pub fn solving_op_combos(perm: &[f64]) -> usize {
+    dump_permutation(perm)
+        .iter()
+        .filter(|(_, _, value)| (value - TARGET).abs() < EPSILON)
+        .count()
+}
+
+/// 对固定顺序的 4 个数字，尝试 `config.ops` 的所有运算符组合与 5 种括号结构。
+///
+/// 这 5 种形态（见 `TreeShape`）对应所有不同的二叉树结构：
+/// 1. `(a op b) op (c op d)`
+/// 2. `((a op b) op c) op d`
+/// 3. `a op (b op (c op d))`
+/// 4. `(a op (b op c)) op d`
+/// 5. `a op ((b op c) op d)`
+///
+/// 每个结构都严格按照计算顺序逐步调用 `apply_op`，当结果按 `config`
+/// 与目标值相等时（默认即与 `TARGET` 在 `EPSILON` 范围内相等），就认为找到了一个正确解。
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(config), fields(target = config.target))
+)]
+fn find_solutions_for_permutation(perm: &[f64], config: &SolverConfig) -> HashMap<String, Expr> {
+    let mut solutions = HashMap::new();
+    for triple in op_triples(&config.ops) {
+        for shape in TreeShape::ALL {
+            if let Some(expr) = try_struct(shape, perm, triple, config) {
+                let text = expr.to_string();
+                #[cfg(feature = "tracing")]
+                tracing::debug!(solution = %text, ?shape, "solution found");
+                solutions.insert(text, expr);
+            }
+        }
+    }
+
+    solutions
+}
+
+// Pure helper shared by the enumerators: returns the expression tree for
+// one structure/operator combination when it matches the configured target.
+fn try_struct(
+    shape: TreeShape,
+    perm: &[f64],
+    ops: [char; 3],
+    config: &SolverConfig,
+) -> Option<Expr> {
+    let matched = if config.integer_intermediates {
+        let result = eval_struct_i64(shape, perm, ops)?;
+        config.target.fract() == 0.0 && result as i128 == config.target as i128
+    } else if config.division == DivisionMode::Exact {
+        config.matches(eval_struct(shape, perm, ops)?)
+    } else {
+        let nums = [perm[0], perm[1], perm[2], perm[3]];
+        let apply = |a, b, op| apply_op_with(a, b, op, config.division);
+        config.matches(eval_shape(shape, nums, ops, apply)?)
+    };
+    matched.then(|| build_struct(shape, perm, ops))
+}
+
+/// 尝试对两个操作数应用运算符，必要时拦截非法操作并返回 `None`。
+///
+/// - 加、减、乘总是有效；
+/// - 除法在分母绝对值小于 `EPSILON` 时直接跳过，以避免除零和数值震荡；
+/// - `None` 会在上层被忽略，从而保证算法的健壮性。
+pub fn apply_op(a: f64, b: f64, op: char) -> Option<f64> {
+    match op {
+        '+' => Some(a + b),
+        '-' => Some(a - b),
+        '*' => Some(a * b),
+        '/' if b.abs() > EPSILON => Some(a / b),
+        _ => None,
+    }
+}
+
+/// 按 `mode` 指定的除法方式应用运算符；加减乘以及除数接近 0 的判断与 `apply_op` 相同。
+pub fn apply_op_with(a: f64, b: f64, op: char, mode: DivisionMode) -> Option<f64> {
+    let value = apply_op(a, b, op)?;
+    Some(match (op, mode) {
+        ('/', DivisionMode::Floor) => value.floor(),
+        ('/', DivisionMode::Round) => value.round(),
+        _ => value,
+    })
+}
+
+/// `apply_op` 的整数版本：加减乘在溢出时返回 `None`，
+/// 除法只在除数非 0 且能整除时有效。
+pub fn apply_op_i64(a: i64, b: i64, op: char) -> Option<i64> {
+    match op {
+        '+' => a.checked_add(b),
+        '-' => a.checked_sub(b),
+        '*' => a.checked_mul(b),
+        '/' if b != 0 && a.checked_rem(b)? == 0 => a.checked_div(b),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::useless_vec, clippy::vec_init_then_push)]
+mod test_vec {
+    #[test]
+    fn arr_basic() {
+        let arr = [10, 20, 30];
+        assert_eq!(arr[0], 10);
+        assert_eq!(arr[1], 20);
+        assert_eq!(arr[2], 30);
+        assert_eq!(arr.len(), 3);
+
+        let arr2 = [&arr[..], &arr[1..=2]];
+
+        assert_eq!(arr2[1], [20, 30]);
+
+        let v = vec![10, 20, 30];
+        assert_eq!(v[0], 10);
+        assert_eq!(v[1], 20);
+        assert_eq!(v[2], 30);
+        assert_eq!(v.len(), 3);
+
+        let mut v2 = Vec::new();
+        v2.push(100);
+        v2.push(200);
+        assert_eq!(v2.len(), 2);
+        assert_eq!(v2[0], 100);
+        assert_eq!(v2[1], 200);
+    }
+
+    #[test]
+    fn test_array_range_collect() {
+        assert_eq!((3..=5), std::ops::RangeInclusive::new(3, 5));
+        assert_eq!((1..2), std::ops::Range { start: 1, end: 2 });
+        assert_eq!(3 + 4 + 5, (3..=5).sum::<i32>());
+        let arr = [0, 1, 2, 3, 4];
+        assert_eq!(arr[..], [0, 1, 2, 3, 4]);
+        assert_eq!(arr[..3], [0, 1, 2]);
+        assert_eq!(arr[..=3], [0, 1, 2, 3]);
+        assert_eq!(arr[1..], [1, 2, 3, 4]);
+        assert_eq!(arr[1..3], [1, 2]);
+        assert_eq!(arr[1..=3], [1, 2, 3]); // This is a `RangeInclusive`
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_op_basic() {
+        assert_eq!(apply_op(2.0, 3.0, '+'), Some(5.0));
+        assert_eq!(apply_op(5.0, 3.0, '-'), Some(2.0));
+        assert_eq!(apply_op(4.0, 3.0, '*'), Some(12.0));
+        assert_eq!(apply_op(8.0, 2.0, '/'), Some(4.0));
+        // division by (near) zero should return None
+        assert_eq!(apply_op(1.0, 1e-9, '/'), None);
+    }
+
+    #[test]
+    fn test_permutations_count() {
+        let nums = vec![1.0, 2.0, 3.0, 4.0];
+        let perms = permutations(&nums);
+        assert_eq!(perms.len(), 24); // 4! = 24
+        let unique_perms: HashSet<_> = perms
+            .into_iter()
+            .map(|p| p.iter().map(|&f| f.to_bits()).collect::<Vec<u64>>())
+            .collect();
+        assert_eq!(unique_perms.len(), 24); // all should be unique
+    }
+
+    #[test]
+    fn test_permutations_count_repeated() {
+        let nums = vec![1.0, 2.0, 2.0];
+        let perms = permutations(&nums);
+        assert_eq!(perms.len(), 6); // 3! / 2! = 3
+        let unique_perms: HashSet<_> = perms
+            .into_iter()
+            .map(|p| p.iter().map(|&f| f.to_bits()).collect::<Vec<u64>>())
+            .collect();
+        assert_eq!(unique_perms.len(), 3); // only 3 unique
+    }
+
+    #[test]
+    fn test_solve_with_ops_subset() {
+        // [3, 3, 8, 8] is only solvable as 8 / (3 - 8 / 3).
+        assert!(!solve_24(&[3, 3, 8, 8]).is_empty());
+        assert!(solve_no_division(&[3, 3, 8, 8]).is_empty());
+
+        let solutions = solve_with_ops(&[6, 2, 3, 4], &['+', '*']);
+        assert!(solutions.contains(&"(6 * 2) + (3 * 4)".to_string()));
+        assert!(solutions
+            .iter()
+            .all(|s| !s.contains('-') && !s.contains('/')));
+    }
+
+    #[test]
+    fn test_solve_subset_matches_solve_24_and_other_sizes() {
+        let mut four = solve_subset(&[6, 2, 3, 4]);
+        let mut expected = solve_24(&[6, 2, 3, 4]);
+        four.sort();
+        expected.sort();
+        assert_eq!(four, expected);
+
+        assert_eq!(solve_subset(&[6, 4]).len(), 2); // 6 * 4 and 4 * 6
+        assert!(solve_subset(&[2, 3, 4]).contains(&"(2 * 3) * 4".to_string()));
+        assert!(solve_subset(&[1, 1, 1]).is_empty());
+        assert!(solve_subset(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_solve_with_config_relative_comparison() {
+        // a * b - c * d: both products exceed 2^53, so f64 rounding leaves the
+        // difference 64 away from the exact target.
+        let cards = [1_700_000_001, 1_900_000_003, 1_800_000_007, 1_794_438_885];
+        let absolute = SolverConfig {
+            target: 10_001_438_927_808.0,
+            ..SolverConfig::default()
+        };
+        assert!(solve_with_config(&cards, &absolute).is_empty());
+
+        let relative = SolverConfig {
+            comparison: config::Comparison::Relative,
+            ..absolute
+        };
+        let solutions = solve_with_config(&cards, &relative);
+        assert!(solutions
+            .contains(&"(1700000001 * 1900000003) - (1800000007 * 1794438885)".to_string()));
+    }
+
+    #[test]
+    fn test_solutions_by_permutation_union() {
+        let grouped = solutions_by_permutation(&[6, 2, 3, 4]);
+        assert!(grouped
+            .iter()
+            .all(|(perm, sols)| perm.len() == 4 && !sols.is_empty()));
+        let union: HashSet<String> = grouped.into_iter().flat_map(|(_, sols)| sols).collect();
+        let expected: HashSet<String> = solve_24(&[6, 2, 3, 4]).into_iter().collect();
+        assert_eq!(union, expected);
+        assert!(solutions_by_permutation(&[1, 1, 1, 1]).is_empty());
+    }
+
+    #[test]
+    fn test_apply_op_i64() {
+        assert_eq!(apply_op_i64(8, 2, '/'), Some(4));
+        assert_eq!(apply_op_i64(8, 3, '/'), None);
+        assert_eq!(apply_op_i64(8, 0, '/'), None);
+        assert_eq!(apply_op_i64(i64::MAX, 2, '*'), None);
+    }
+
+    #[test]
+    fn test_integer_intermediates_are_exact() {
+        let integer = SolverConfig {
+            integer_intermediates: true,
+            ..SolverConfig::default()
+        };
+        // 8 / (3 - 8 / 3) needs the fraction 8/3.
+        assert!(solve_with_config(&[3, 3, 8, 8], &integer).is_empty());
+        assert!(!solve_with_config(&[6, 2, 3, 4], &integer).is_empty());
+
+        // f64 rounds both products and misses the exact difference; i64 does not.
+        let cards = [1_700_000_001, 1_900_000_003, 1_800_000_007, 1_794_438_885];
+        let big_target = SolverConfig {
+            target: 10_001_438_927_808.0,
+            ..SolverConfig::default()
+        };
+        assert!(solve_with_config(&cards, &big_target).is_empty());
+        let exact = SolverConfig {
+            integer_intermediates: true,
+            ..big_target
+        };
+        assert!(!solve_with_config(&cards, &exact).is_empty());
+
+        // Overflowing products are rejected instead of panicking.
+        let huge = [i32::MAX; 4];
+        assert!(solve_with_config(&huge, &integer).is_empty());
+    }
+
+    #[test]
+    fn test_is_solvable() {
+        assert!(is_solvable(&[3, 3, 8, 8]));
+        assert!(is_solvable(&[6, 2, 3, 4]));
+        assert!(!is_solvable(&[1, 1, 1, 1]));
+    }
+
+    #[test]
+    fn test_build_struct_formats() {
+        let perm = [1.0, 2.0, 3.0, 4.0];
+        let ops = ['+', '-', '*'];
+        let rendered: Vec<String> = TreeShape::ALL
+            .iter()
+            .map(|&shape| build_struct(shape, &perm, ops).to_string())
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "(1 + 2) - (3 * 4)",
+                "((1 + 2) - 3) * 4",
+                "1 + (2 - (3 * 4))",
+                "(1 + (2 - 3)) * 4",
+                "1 + ((2 - 3) * 4)",
+            ]
+        );
+        for shape in TreeShape::ALL {
+            let expr = build_struct(shape, &perm, ops);
+            assert_eq!(expr::eval(&expr), eval_struct(shape, &perm, ops));
+        }
+    }
+
+    #[test]
+    fn test_dump_permutation() {
+        let perm = [6.0, 2.0, 3.0, 3.0];
+        let dump = dump_permutation(&perm);
+        assert_eq!(dump.len(), 5 * 64);
+        assert!(dump.contains(&(TreeShape::Balanced, ['*', '+', '*'], 21.0)));
+        // 6 + (2 / (3 - 3)) divides by zero.
+        let (_, _, invalid) = dump
+            .iter()
+            .find(|(shape, ops, _)| *shape == TreeShape::RightChain && *ops == ['+', '/', '-'])
+            .unwrap();
+        assert!(invalid.is_nan());
+    }
+
+    #[test]
+    fn test_enumerate_tree_shapes() {
+        let counts: Vec<usize> = (1..=5).map(enumerate_tree_shapes).collect();
+        assert_eq!(counts, vec![1, 1, 2, 5, 14]);
+        assert_eq!(enumerate_tree_shapes(4), TreeShape::ALL.len());
+        assert_eq!(enumerate_tree_shapes(0), 0);
+    }
+
+    #[test]
+    fn test_solving_op_combos() {
+        let rich = solving_op_combos(&[6.0, 6.0, 6.0, 6.0]);
+        let sparse = solving_op_combos(&[8.0, 3.0, 8.0, 3.0]);
+        assert_eq!(sparse, 1);
+        assert!(rich > sparse, "{} > {}", rich, sparse);
+        assert_eq!(solving_op_combos(&[1.0, 1.0, 1.0, 1.0]), 0);
+    }
+
+    #[test]
+    fn test_try_struct1_success_and_failure() {
+        let perm = [6.0, 2.0, 3.0, 4.0];
+        // (6 * 2) + (3 * 4) == 24
+        assert!(try_struct(
+            TreeShape::Balanced,
+            &perm,
+            ['*', '+', '*'],
+            &SolverConfig::default()
+        )
+        .is_some());
+        assert_eq!(
+            try_struct(
+                TreeShape::Balanced,
+                &perm,
+                ['*', '+', '*'],
+                &SolverConfig::default()
+            )
+            .map(|expr| expr.to_string())
+            .as_deref(),
+            Some("(6 * 2) + (3 * 4)")
+        );
+        // wrong ops shouldn't match
+        assert!(try_struct(
+            TreeShape::Balanced,
+            &perm,
+            ['+', '+', '+'],
+            &SolverConfig::default()
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_try_struct2_success() {
+        let perm = [2.0, 3.0, 4.0, 1.0];
+        // ((2 * 3) * 4) * 1 == 24
+        assert!(try_struct(
+            TreeShape::LeftChain,
+            &perm,
+            ['*', '*', '*'],
+            &SolverConfig::default()
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_try_struct3_success() {
+        let perm = [3.0, 2.0, 4.0, 1.0];
+        // 3 * (2 * (4 * 1)) == 24
+        assert!(try_struct(
+            TreeShape::RightChain,
+            &perm,
+            ['*', '*', '*'],
+            &SolverConfig::default()
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_try_struct4_success() {
+        let perm = [2.0, 3.0, 4.0, 1.0];
+        // (2 * (3 * 4)) * 1 == 24
+        assert!(try_struct(
+            TreeShape::LeftInner,
+            &perm,
+            ['*', '*', '*'],
+            &SolverConfig::default()
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_try_struct5_success() {
+        let perm = [3.0, 2.0, 2.0, 2.0];
+        // 3 * ((2 * 2) * 2) == 24
+        assert!(try_struct(
+            TreeShape::RightInner,
+            &perm,
+            ['*', '*', '*'],
+            &SolverConfig::default()
+        )
+        .is_some());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_solve_24_emits_tracing_events() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        // Counts events and hands out span ids; just enough to observe the solver.
+        struct Counter {
+            events: Arc<AtomicUsize>,
+            spans: AtomicUsize,
+        }
+
+        impl Subscriber for Counter {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(self.spans.fetch_add(1, Ordering::Relaxed) as u64 + 1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &Event<'_>) {
+                self.events.fetch_add(1, Ordering::Relaxed);
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let events = Arc::new(AtomicUsize::new(0));
+        let counter = Counter {
+            events: Arc::clone(&events),
+            spans: AtomicUsize::new(0),
+        };
+        let solutions = tracing::subscriber::with_default(counter, || solve_24(&[3, 3, 8, 8]));
+        assert_eq!(solutions, vec!["8 / (3 - (8 / 3))"]);
+        // One event per permutation plus one per solution hit.
+        assert!(events.load(Ordering::Relaxed) > 24);
+    }
+}
@@ -0,0 +1,505 @@
+//! 日志文件的读取与校验。
+//!
+//! 命令行程序写出的每条记录形如：
+//!
+//! ```text
+//! [2025-11-18 12:34:56] Cards: [9, 13, 12, 6]
+//! Solutions:
+//! (9 * (13 - (12 / 6)))
+//! --------------------
+//! ```
+//!
+//! 无解时 `Solutions:` 及其后的表达式换成一行 `No solution found.`。
+//! 随机抽牌时记录头之后还有一行 `Seed: N`，记录抽牌所用的种子，
+//! 用 `--seed N` 重新运行即可复现这手牌。
+//!
+//! 以 `--format jsonl` 运行时，每手牌改为追加一行 JSON（见 `JsonRecord`），
+//! 便于日志采集系统逐行读取。
+
+use crate::answer::uses_cards;
+use crate::card::validate_hand;
+use crate::config::SolverConfig;
+use crate::expr::{eval, parse_expr};
+use crate::solve_with_config;
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// 日志中每条记录结尾的分隔线。
+pub const SEPARATOR: &str = "--------------------";
+
+/// 文本日志的格式：分隔线、时间戳格式与记录头模板。
+///
+/// `Default` 与命令行程序一直以来的输出完全一致。记录头模板中的 `{ts}`
+/// 替换为时间戳，`{cards}` 替换为 `[3, 3, 8, 8]` 形式的牌面。
+/// 命令行程序从 `solver.toml` 的 `[log]` 表（即 `SolverConfig::log`）读取它。
+/// `verify_log` 只认识默认格式的日志，其他格式用 `verify_log_with` 传入同一份配置校验。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFormat {
+    pub separator: String,
+    /// `chrono` 的 `strftime` 格式串；不合法的格式串会让 `timestamp` panic，
+    /// 来自外部的配置应先用 `validate` 检查。
+    pub timestamp_format: String,
+    pub header: String,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        LogFormat {
+            separator: SEPARATOR.to_string(),
+            timestamp_format: "%Y-%m-%d %H:%M:%S".to_string(),
+            header: "[{ts}] Cards: {cards}".to_string(),
+        }
+    }
+}
+
+impl LogFormat {
+    /// 检查 `timestamp_format` 是否是 `chrono` 能识别的 `strftime` 格式串，
+    /// 以及记录头模板中是否有 `{cards}`（否则无法校验这样的日志）。
+    pub fn validate(&self) -> Result<(), String> {
+        if StrftimeItems::new(&self.timestamp_format).any(|item| item == Item::Error) {
+            Err(format!(
+                "invalid timestamp format '{}'",
+                self.timestamp_format
+            ))
+        } else if !self.header.contains("{cards}") {
+            Err(format!("log header '{}' has no {{cards}}", self.header))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 按 `timestamp_format` 格式化时间戳。
+    pub fn timestamp(&self, time: &NaiveDateTime) -> String {
+        time.format(&self.timestamp_format).to_string()
+    }
+
+    /// 渲染一手牌的完整记录（含结尾的分隔线与换行）；`seed` 为抽牌所用的种子。
+    pub fn entry(
+        &self,
+        time: &NaiveDateTime,
+        cards: &[i32],
+        seed: Option<u64>,
+        solutions: &[String],
+    ) -> String {
+        let header = self
+            .header
+            .replace("{ts}", &self.timestamp(time))
+            .replace("{cards}", &format!("{:?}", cards));
+        let mut out = format!("{}\n", header);
+        if let Some(seed) = seed {
+            out.push_str(&format!("Seed: {}\n", seed));
+        }
+        if solutions.is_empty() {
+            out.push_str("No solution found.\n");
+        } else {
+            out.push_str("Solutions:\n");
+            for s in solutions {
+                out.push_str(s);
+                out.push('\n');
+            }
+        }
+        out.push_str(&self.separator);
+        out.push('\n');
+        out
+    }
+}
+
+/// JSONL 日志中的一条记录，对应一手牌。
+///
+/// 序列化后形如
+/// `{"ts":"2025-11-18 12:34:56","cards":[3,3,8,8],"count":1,"solutions":["8 / (3 - (8 / 3))"]}`。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRecord {
+    pub ts: String,
+    pub cards: Vec<i32>,
+    /// 随机抽牌所用的种子；手动给出的牌没有种子，此时不输出该字段。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    pub count: usize,
+    pub solutions: Vec<String>,
+}
+
+impl JsonRecord {
+    pub fn new(ts: impl Into<String>, cards: &[i32], solutions: &[String]) -> JsonRecord {
+        JsonRecord {
+            ts: ts.into(),
+            cards: cards.to_vec(),
+            seed: None,
+            count: solutions.len(),
+            solutions: solutions.to_vec(),
+        }
+    }
+
+    /// 附上抽牌所用的种子。
+    pub fn with_seed(mut self, seed: Option<u64>) -> JsonRecord {
+        self.seed = seed;
+        self
+    }
+
+    /// 序列化为单行 JSON（不含结尾换行），可以直接追加到 JSONL 文件。
+    pub fn to_jsonl(&self) -> String {
+        serde_json::to_string(self).expect("a record of strings and integers always serializes")
+    }
+}
+
+/// 校验日志时发现的一处问题，`line` 为日志中的行号（从 1 开始）。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Discrepancy {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// Match `line` against a header template in which `{ts}` and `{cards}` each
+// stand for any non-empty text; returns the text in place of `{cards}`.
+fn match_header<'a>(template: &str, line: &'a str) -> Option<&'a str> {
+    fn walk<'a>(template: &str, line: &'a str, cards: Option<&'a str>) -> Option<&'a str> {
+        if template.is_empty() {
+            return if line.is_empty() { cards } else { None };
+        }
+        for (placeholder, is_cards) in [("{ts}", false), ("{cards}", true)] {
+            if let Some(rest) = template.strip_prefix(placeholder) {
+                return (1..=line.len())
+                    .filter(|&i| line.is_char_boundary(i))
+                    .find_map(|i| {
+                        let cards = if is_cards { Some(&line[..i]) } else { cards };
+                        walk(rest, &line[i..], cards)
+                    });
+            }
+        }
+        let c = template.chars().next()?;
+        walk(&template[c.len_utf8()..], line.strip_prefix(c)?, cards)
+    }
+    walk(template, line, None)
+}
+
+/// 从记录头中 `{cards}` 对应的 `[a, b, c, d]` 文本取出牌面。
+fn parse_cards(list: &str) -> Option<Vec<i32>> {
+    let inner = list.trim().strip_prefix('[')?.strip_suffix(']')?;
+    inner.split(',').map(|s| s.trim().parse().ok()).collect()
+}
+
+// One hand read from the log: its header line, cards and logged solutions
+// (each with the line it was found on).
+struct LoggedEntry {
+    header: usize,
+    cards: Vec<i32>,
+    solutions: Vec<(usize, String)>,
+}
+
+impl LoggedEntry {
+    fn unterminated(&self) -> Discrepancy {
+        Discrepancy {
+            line: self.header,
+            message: "entry is not terminated by a separator".to_string(),
+        }
+    }
+}
+
+// Compare one logged hand against the current solver and report differences.
+fn check_entry(entry: &LoggedEntry, config: &SolverConfig, out: &mut Vec<Discrepancy>) {
+    let cards = &entry.cards;
+    // The solver indexes four cards, so a malformed header must not reach it.
+    if let Err(e) = validate_hand(cards) {
+        out.push(Discrepancy {
+            line: entry.header,
+            message: format!("invalid hand {:?}: {}", cards, e),
+        });
+        return;
+    }
+    let current: HashSet<String> = solve_with_config(cards, config).into_iter().collect();
+    for (line, solution) in &entry.solutions {
+        if current.contains(solution) {
+            continue;
+        }
+        let message = match parse_expr(solution) {
+            Err(e) => format!("cannot parse '{}': {}", solution, e),
+            Ok(expr)
+                if !uses_cards(&expr, cards) || !eval(&expr).is_some_and(|v| config.matches(v)) =>
+            {
+                format!(
+                    "'{}' does not evaluate to {} with {:?}",
+                    solution, config.target, cards
+                )
+            }
+            Ok(_) => format!("'{}' is not produced by the current solver", solution),
+        };
+        out.push(Discrepancy {
+            line: *line,
+            message,
+        });
+    }
+
+    let logged_set: HashSet<&str> = entry.solutions.iter().map(|(_, s)| s.as_str()).collect();
+    let mut missing: Vec<&String> = current
+        .iter()
+        .filter(|s| !logged_set.contains(s.as_str()))
+        .collect();
+    missing.sort();
+    for solution in missing {
+        out.push(Discrepancy {
+            line: entry.header,
+            message: format!("solver now also finds '{}' for {:?}", solution, cards),
+        });
+    }
+}
+
+/// 重新求解日志中记录的每一手牌，报告与当前求解器不一致的地方。
+///
+/// 检查内容包括：记录的每个解能否解析、是否真的用这手牌算出 24、
+/// 当前求解器是否仍会给出它；以及当前求解器给出、但日志里没有的解。
+/// 无法识别的记录头、张数或点数不合法的牌以及未结束的记录也会作为问题报告。
+///
+/// 按标准规则求解，即 `verify_log_with(content, &SolverConfig::default())`。
+pub fn verify_log(content: &str) -> Vec<Discrepancy> {
+    verify_log_with(content, &SolverConfig::default())
+}
+
+/// 与 `verify_log` 相同，但按 `config` 重新求解（`solve_with_config`）。
+///
+/// 命令行程序写日志时使用 `solver.toml` 中的配置，校验时应传入同一份配置，
+/// 否则非默认规则下写出的解都会被误报为不一致。记录头与分隔线也按
+/// `config.log` 识别：记录头模板中的 `{ts}` 可以对应任意文本，`{cards}` 对应牌面。
+pub fn verify_log_with(content: &str, config: &SolverConfig) -> Vec<Discrepancy> {
+    let format = &config.log;
+    let mut discrepancies = Vec::new();
+    let mut entry: Option<LoggedEntry> = None;
+
+    for (index, raw) in content.lines().enumerate() {
+        let line_no = index + 1;
+        let line = raw.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(list) = match_header(format.header.trim(), line) {
+            if let Some(previous) = entry.take() {
+                discrepancies.push(previous.unterminated());
+            }
+            match parse_cards(list) {
+                Some(cards) => {
+                    entry = Some(LoggedEntry {
+                        header: line_no,
+                        cards,
+                        solutions: Vec::new(),
+                    })
+                }
+                None => discrepancies.push(Discrepancy {
+                    line: line_no,
+                    message: "cannot read cards from header".to_string(),
+                }),
+            }
+            continue;
+        }
+        let Some(current) = entry.as_mut() else {
+            continue;
+        };
+        match line {
+            separator if separator == format.separator.trim() => {
+                check_entry(current, config, &mut discrepancies);
+                entry = None;
+            }
+            "Solutions:" | "No solution found." => {}
+            seed if seed.starts_with("Seed:") => {}
+            solution => current.solutions.push((line_no, solution.to_string())),
+        }
+    }
+
+    if let Some(last) = entry {
+        discrepancies.push(last.unterminated());
+    }
+    discrepancies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve_24;
+
+    #[test]
+    fn test_verify_log_accepts_current_output() {
+        let mut log = String::from("[2025-11-18 12:34:56] Cards: [3, 3, 8, 8]\nSolutions:\n");
+        for s in solve_24(&[3, 3, 8, 8]) {
+            log.push_str(&s);
+            log.push('\n');
+        }
+        log.push_str("--------------------\n");
+        log.push_str(
+            "[2025-11-18 12:35:00] Cards: [1, 1, 1, 1]\nNo solution found.\n--------------------\n",
+        );
+        assert_eq!(verify_log(&log), vec![]);
+    }
+
+    #[test]
+    fn test_verify_log_reports_mismatches_with_line_numbers() {
+        let log = "\
+[2025-11-18 12:34:56] Cards: [3, 3, 8, 8]
+Solutions:
+8 / (3 - (8 / 3))
+(3 + 3) * (8 - 8)
+8 / (3 -
+--------------------
+[2025-11-18 12:35:00] Cards: [3, 3, 8, 8]
+No solution found.
+--------------------
+";
+        let found = verify_log(log);
+        let lines: Vec<usize> = found.iter().map(|d| d.line).collect();
+        assert_eq!(lines, vec![4, 5, 7]);
+        assert!(found[0].message.contains("does not evaluate to 24"));
+        assert!(found[1].message.contains("cannot parse"));
+        assert!(found[2].message.contains("8 / (3 - (8 / 3))"));
+    }
+
+    #[test]
+    fn test_json_record_lines_parse_independently() {
+        let mut log = String::new();
+        for cards in [[3, 3, 8, 8], [1, 1, 1, 1]] {
+            let solutions = solve_24(&cards);
+            let record = JsonRecord::new("2025-11-18 12:34:56", &cards, &solutions);
+            log.push_str(&record.to_jsonl());
+            log.push('\n');
+        }
+
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let first: JsonRecord = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first.cards, vec![3, 3, 8, 8]);
+        assert_eq!(first.count, 1);
+        assert_eq!(first.solutions, vec!["8 / (3 - (8 / 3))".to_string()]);
+        let second: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second["count"], 0);
+        assert_eq!(second["ts"], "2025-11-18 12:34:56");
+    }
+
+    fn sample_time() -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(2025, 11, 18)
+            .unwrap()
+            .and_hms_opt(12, 34, 56)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_log_format_default_matches_verify() {
+        let solutions = solve_24(&[3, 3, 8, 8]);
+        let entry = LogFormat::default().entry(&sample_time(), &[3, 3, 8, 8], None, &solutions);
+        assert_eq!(
+            entry,
+            "[2025-11-18 12:34:56] Cards: [3, 3, 8, 8]\nSolutions:\n8 / (3 - (8 / 3))\n--------------------\n"
+        );
+        assert_eq!(verify_log(&entry), vec![]);
+    }
+
+    #[test]
+    fn test_log_format_custom() {
+        let format = LogFormat {
+            separator: "===".to_string(),
+            timestamp_format: "%d/%m/%Y".to_string(),
+            header: "hand {cards} @ {ts}".to_string(),
+        };
+        assert_eq!(format.validate(), Ok(()));
+        let entry = format.entry(&sample_time(), &[1, 1, 1, 1], None, &[]);
+        let lines: Vec<&str> = entry.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "hand [1, 1, 1, 1] @ 18/11/2025",
+                "No solution found.",
+                "==="
+            ]
+        );
+
+        let broken = LogFormat {
+            timestamp_format: "%Y-%Q".to_string(),
+            ..LogFormat::default()
+        };
+        assert!(broken.validate().is_err());
+    }
+
+    #[test]
+    fn test_seed_line_is_logged_and_accepted() {
+        let solutions = solve_24(&[6, 2, 3, 4]);
+        let entry = LogFormat::default().entry(&sample_time(), &[6, 2, 3, 4], Some(42), &solutions);
+        assert_eq!(entry.lines().nth(1), Some("Seed: 42"));
+        assert_eq!(verify_log(&entry), vec![]);
+
+        let record = JsonRecord::new("t", &[1, 1, 1, 1], &[]);
+        assert!(!record.to_jsonl().contains("seed"));
+        let record = record.with_seed(Some(42));
+        assert!(record.to_jsonl().contains(r#""seed":42"#));
+    }
+
+    #[test]
+    fn test_verify_log_rejects_wrong_card_count() {
+        let log = "\
+[2025-11-18 12:34:56] Cards: [3, 8, 8]
+No solution found.
+--------------------
+[2025-11-18 12:35:00] Cards: [3, 3, 8, 14]
+No solution found.
+--------------------
+";
+        let found = verify_log(log);
+        let lines: Vec<usize> = found.iter().map(|d| d.line).collect();
+        assert_eq!(lines, vec![1, 4]);
+        assert!(found[0].message.contains("expected 4 cards, got 3"));
+        assert!(found[1].message.contains("not in 1..=13"));
+    }
+
+    #[test]
+    fn test_verify_log_with_config() {
+        let config = SolverConfig {
+            target: 36.0,
+            ops: vec!['+', '*'],
+            ..SolverConfig::default()
+        };
+        let solutions = solve_with_config(&[1, 2, 3, 4], &config);
+        assert!(!solutions.is_empty());
+        let entry = LogFormat::default().entry(&sample_time(), &[1, 2, 3, 4], None, &solutions);
+        assert_eq!(verify_log_with(&entry, &config), vec![]);
+        // Under the default rules the same log is full of false discrepancies.
+        assert!(!verify_log(&entry).is_empty());
+    }
+
+    #[test]
+    fn test_verify_log_with_custom_format() {
+        let config = SolverConfig {
+            log: LogFormat {
+                separator: "===".to_string(),
+                timestamp_format: "%d/%m/%Y".to_string(),
+                header: "hand {cards} @ {ts}".to_string(),
+            },
+            ..SolverConfig::default()
+        };
+        let mut log = String::new();
+        for cards in [[3, 3, 8, 8], [1, 1, 1, 1], [6, 2, 3, 4]] {
+            let solutions = solve_with_config(&cards, &config);
+            log.push_str(
+                &config
+                    .log
+                    .entry(&sample_time(), &cards, Some(7), &solutions),
+            );
+        }
+        assert_eq!(verify_log_with(&log, &config), vec![]);
+
+        // A wrong solution is still caught under the custom format.
+        let tampered = log.replacen("8 / (3 - (8 / 3))", "(3 + 3) * (8 - 8)", 1);
+        let found = verify_log_with(&tampered, &config);
+        assert_eq!(found.len(), 2, "{:?}", found);
+        assert!(found[0].message.contains("does not evaluate to 24"));
+
+        let headless = LogFormat {
+            header: "[{ts}]".to_string(),
+            ..LogFormat::default()
+        };
+        assert!(headless.validate().is_err());
+    }
+}
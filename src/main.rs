@@ -1,301 +1,341 @@
-//! # 程序说明
+//! 24 点命令行程序：随机抽一手牌、求解，并把结果追加写入 `log/24_game_log.txt`。
 //!
-//! 这是一个 24 点求解器：程序会随机抽取 4 张扑克牌（数值 1~13），
-//! 使用加减乘除与所有括号组合来寻找得到 24 的表达式，
-//! 并把 "有解" 或 "无解" 的结果写入 `log/24_game_log.txt` 日志。
+//! 求解算法的说明见库文档（`src/lib.rs`）。
 //!
-//! ## 算法完整性与正确性
-//! - **完整性**：对 4 张牌进行全排列，共 4! = 24 种顺序；
-//!   每一顺序都会尝试 3 个运算符位的所有 4^3 组合；
-//!   同时覆盖五种合法的二叉树括号形态，等价于枚举所有四元表达式结构。
-//!   因此任何合法的 24 点表达式必定会被枚举到。
-//! - **正确性**：所有运算在 `f64` 中完成，并使用 `EPSILON` 进行浮点比较；
-//!   除法在分母绝对值小于 `EPSILON` 时会被忽略以避免除以零。
-//!   这些约束确保枚举到的表达式都是真实可计算且确实等于 24 的结果。
+//! 命令行参数：
+//! - `--verify FILE`：不抽牌，而是重新校验一份已有的日志文件并报告不一致之处，
+//!   校验时使用与写日志相同的 `solver.toml` 配置。
+//! - `--format text|jsonl`：日志格式，默认 `text`；`jsonl` 时每手牌向
+//!   `log/24_game_log.jsonl` 追加一行 JSON。
+//! - `--oneline`：标准输出改为一行以 ` | ` 分隔的摘要（牌、是否有解、解数、最简解），
+//!   便于 `awk`/`grep` 处理；摘要与日志使用同一次求解的结果，日志照常写入。
+//!
+//! - `--count N`：连续处理 `N` 手随机牌，默认 1；不能与位置参数 `CARDS` 同时使用。
+//! - `--time`：额外打印每手牌的求解耗时，只计求解本身，不含读写配置与日志；
+//!   处理多手牌时最后再打印总耗时与平均耗时。
+//! - `--summary FILE`：运行结束时把本次处理的牌的汇总（`SessionStats`）写入 `FILE`。
+//! - `--seed N`：用给定的种子抽牌，复现日志中 `Seed: N` 那一手牌；
+//!   与 `--count` 同时使用时，第 `i` 手（从 0 开始）的种子为 `N + i`。
+//! - 位置参数 `CARDS`：不随机抽牌，改为求解给定的牌，如 `3 3 8 8`、`"3 3 8 8"`
+//!   或简写 `3388`（见 `parse_hand`）。牌也可以写作 `A`/`J`/`Q`/`K`；必须恰好 4 张 1~13 的牌。
+//! - `--faces standard|blackjack`：`J`/`Q`/`K` 的算法，默认 `standard`（11/12/13），
+//!   `blackjack` 时都算 10。
+//!
+//! 如果当前目录下存在 `solver.toml`，求解时使用其中的 `SolverConfig` 设置，
+//! 文本日志的格式取自其中的 `[log]` 表（见 `LogFormat`）。
 
 use chrono::Local;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
-use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::time::{Duration, Instant};
+use twelve_four_puzzle::analysis::best_of;
+use twelve_four_puzzle::card::{parse_card, validate_hand, CardValueMap};
+use twelve_four_puzzle::config::{load_config, SolverConfig};
+use twelve_four_puzzle::draw::draw;
+use twelve_four_puzzle::logging::{verify_log_with, JsonRecord};
+use twelve_four_puzzle::solution::Solution;
+use twelve_four_puzzle::solve_with_config_full;
+use twelve_four_puzzle::stats::SessionStats;
+
+/// 可选的求解器配置文件，存在时覆盖默认规则。
+const CONFIG_PATH: &str = "solver.toml";
+
+/// 日志的写出格式。
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum OutputFormat {
+    /// 原有的多行文本记录，写入 `log/24_game_log.txt`。
+    #[default]
+    Text,
+    /// 每手牌一行 JSON，写入 `log/24_game_log.jsonl`。
+    Jsonl,
+}
 
-const TARGET: f64 = 24.0;
-const EPSILON: f64 = 1e-6;
-
-/// 程序入口：抽牌、求解、并把结果写入日志。
-///
-/// 这里的流程是：
-/// 1. 打开（或创建）日志文件并定位到末尾；
-/// 2. 随机抽取 4 张牌；
-/// 3. 调用 `solve_24` 获取所有表达式；
-/// 4. 按时间戳记录抽到的牌和对应的所有解，若无解则写入提示。
-fn main() {
-    // Ensure the `log` directory exists so opening the file won't fail.
-    std::fs::create_dir_all("log").expect("Failed to create log directory");
-
-    let mut log_file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("log/24_game_log.txt")
-        .expect("Failed to open log file");
-    // Run a single hand (generate, solve, log) and then exit.
-    let mut cards = (1..=13).collect::<Vec<i32>>();
-    let mut rng = thread_rng();
-    cards.shuffle(&mut rng);
-    let hand: Vec<i32> = cards.into_iter().take(4).collect();
-
-    let solutions = solve_24(&hand);
-
-    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-    writeln!(log_file, "[{}] Cards: {:?}", timestamp, hand).unwrap();
-
-    if solutions.is_empty() {
-        writeln!(log_file, "No solution found.").unwrap();
-    } else {
-        writeln!(log_file, "Solutions:").unwrap();
-        for s in &solutions {
-            writeln!(log_file, "{}", s).unwrap();
+impl OutputFormat {
+    fn log_path(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "log/24_game_log.txt",
+            OutputFormat::Jsonl => "log/24_game_log.jsonl",
         }
     }
-    writeln!(log_file, "--------------------").unwrap();
-    println!(
-        "Processed hand: {:3?}.\t Solution count {:12}.",
-        hand,
-        solutions.len()
-    );
+}
 
-    // println!("Log file has been updated.");
+/// 解析后的命令行参数。
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    /// `--verify FILE`：要校验的日志文件。
+    verify: Option<String>,
+    /// `--format text|jsonl`：日志格式。
+    format: OutputFormat,
+    /// `--oneline`：标准输出只打印一行摘要。
+    oneline: bool,
+    /// 位置参数给出的牌；为 `None` 时随机抽牌。
+    cards: Option<Vec<i32>>,
+    /// `--seed N`：抽牌所用的种子，缺省时随机生成。
+    seed: Option<u64>,
+    /// `--summary FILE`：汇总文件的路径。
+    summary: Option<String>,
+    /// `--time`：打印求解耗时。
+    time: bool,
+    /// `--count N`：处理的手数，为 `None` 时只处理一手。
+    count: Option<usize>,
+    /// `--faces standard|blackjack`：位置参数中 `J`/`Q`/`K` 的换算规则。
+    faces: CardValueMap,
 }
 
-/// 对给定的 4 张牌，返回所有可得到 24 的表达式。
-///
-/// 为了确保覆盖所有组合，先将牌转为 `f64` 并生成全排列，
-/// 再对每一个排列调用 `find_solutions_for_permutation` 来遍历
-/// 运算符与括号结构。使用 `HashSet` 避免重复表达式。
-fn solve_24(cards: &[i32]) -> Vec<String> {
-    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
-
-    let mut all_solutions = HashSet::new();
-    for perm in permutations(&nums) {
-        let sols = find_solutions_for_permutation(&perm);
-        all_solutions.extend(sols);
-    }
-    all_solutions.into_iter().collect()
+/// 用 `seed` 初始化 `StdRng` 并抽一手牌；同一种子总是得到同一手牌。
+fn hand_for_seed(seed: u64) -> Vec<i32> {
+    draw(&mut StdRng::seed_from_u64(seed))
 }
 
-/// 返回 `nums` 的所有排列（每个排列为 `Vec<f64>`）。
+/// 解析命令行给出的牌。
 ///
-/// 详细说明：
-/// - 该函数以递归方式实现。对于非空输入，函数会枚举每个位置 `i` 作为当前头元素 `v`，
-///   构造剩余元素 `rest`（去掉索引 `i` 的元素），递归计算 `rest` 的所有排列，
-///   然后把 `v` 置于每个子排列的头部，得到完整排列列表。
-/// - 基准情形：当 `nums` 为空时，返回 `vec![vec![]]`，即包含一个空排列，这样递归拼接时能正确回溯。
-/// - 风格与性能：该实现是函数式的——不依赖外部可变状态或回调，返回新分配的数据结构，
-///   因而易于理解与测试。其时间复杂度为 O(n! * n)，空间复杂度也为 O(n!)（因为要保存所有排列），
-///   对本程序的 n=4 情形而言开销可忽略。
+/// 只有一个参数且恰好是 4 个 1~9 的数字时按简写处理，每个数字一张牌，
+/// 例如 `3388` 即 `[3, 3, 8, 8]`。含 10~13 的牌无法用简写表示
+/// （`1011` 既可能是 `[10, 11]` 也可能是 `[1, 0, 1, 1]`），此时需要用空白分隔，
+/// 例如 `10 11 1 2` 或 `"10 11 1 2"`。
 ///
-/// 示例：
-/// ```rust
-/// let perms = permutations(&[1.0, 2.0, 3.0]);
-/// // `perms` 将包含 6 个排列：
-/// // [1.0, 2.0, 3.0]
-/// // [1.0, 3.0, 2.0]
-/// // [2.0, 1.0, 3.0]
-/// // [2.0, 3.0, 1.0]
-/// // [3.0, 1.0, 2.0]
-/// // [3.0, 2.0, 1.0]
-/// ```
-fn permutations(nums: &[f64]) -> Vec<Vec<f64>> {
-    if nums.is_empty() {
-        return vec![vec![]];
+/// 数字与 `A`/`J`/`Q`/`K` 一样都是牌面，统一用 `parse_card` 按 `faces` 换算，
+/// 因此 `blackjack` 规则下 `13` 与 `K` 都算 10。
+/// 换算后必须恰好是 4 张 1~13 的牌（见 `validate_hand`），否则返回 `Err`。
+fn parse_hand(tokens: &[String], faces: CardValueMap) -> Result<Vec<i32>, String> {
+    if let [token] = tokens {
+        if token.len() == 4 && token.chars().all(|c| ('1'..='9').contains(&c)) {
+            return Ok(token.chars().map(|c| faces.value(c as u8 - b'0')).collect());
+        }
     }
+    let hand: Vec<i32> = tokens
+        .iter()
+        .flat_map(|t| t.split_whitespace())
+        .map(|t| {
+            // Numbers outside 1..=13 are kept so `validate_hand` can name them.
+            parse_card(t, faces)
+                .or_else(|| t.parse().ok())
+                .ok_or_else(|| format!("invalid card '{}'", t))
+        })
+        .collect::<Result<_, _>>()?;
+    validate_hand(&hand).map_err(|e| e.to_string())?;
+    Ok(hand)
+}
 
-    let mut result = Vec::new();
-    for (i, &v) in nums.iter().enumerate() {
-        let mut rest = nums.to_vec();
-        rest.remove(i);
-        for mut perm in permutations(&rest) {
-            perm.insert(0, v);
-            result.push(perm);
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut positional = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--verify" => {
+                let path = iter.next().ok_or("--verify requires a file path")?;
+                options.verify = Some(path.clone());
+            }
+            "--oneline" => options.oneline = true,
+            "--summary" => {
+                let path = iter.next().ok_or("--summary requires a file path")?;
+                options.summary = Some(path.clone());
+            }
+            "--time" => options.time = true,
+            "--count" => {
+                let count = iter.next().ok_or("--count requires a number")?;
+                let count = count
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid count '{}'", count))?;
+                options.count = Some(count);
+            }
+            "--seed" => {
+                let seed = iter.next().ok_or("--seed requires a number")?;
+                let seed = seed
+                    .parse()
+                    .map_err(|_| format!("invalid seed '{}'", seed))?;
+                options.seed = Some(seed);
+            }
+            "--format" => {
+                options.format = match iter.next().map(String::as_str) {
+                    Some("text") => OutputFormat::Text,
+                    Some("jsonl") => OutputFormat::Jsonl,
+                    Some(other) => return Err(format!("unknown format '{}'", other)),
+                    None => return Err("--format requires text or jsonl".to_string()),
+                };
+            }
+            "--faces" => {
+                options.faces = match iter.next().map(String::as_str) {
+                    Some("standard") => CardValueMap::Standard,
+                    Some("blackjack") => CardValueMap::Blackjack,
+                    Some(other) => return Err(format!("unknown face mapping '{}'", other)),
+                    None => return Err("--faces requires standard or blackjack".to_string()),
+                };
+            }
+            other if other.starts_with("--") => {
+                return Err(format!("unknown argument '{}'", other))
+            }
+            _ => positional.push(arg.clone()),
         }
     }
-
-    result
+    if !positional.is_empty() {
+        if options.count.is_some() {
+            return Err("--count cannot be combined with explicit cards".to_string());
+        }
+        options.cards = Some(parse_hand(&positional, options.faces)?);
+    }
+    Ok(options)
 }
 
-/// 对固定顺序的 4 个数字，尝试所有运算符组合与 5 种括号结构。
-///
-/// 这 5 种形态对应所有不同的二叉树结构：
-/// 1. `(a op b) op (c op d)`
-/// 2. `((a op b) op c) op d`
-/// 3. `a op (b op (c op d))`
-/// 4. `(a op (b op c)) op d`
-/// 5. `a op ((b op c) op d)`
+/// 程序入口：抽牌、求解、并把结果写入日志。
 ///
-/// 每个结构都严格按照计算顺序逐步调用 `apply_op`，当结果与 `TARGET`
-/// 在 `EPSILON` 范围内相等时，即认为找到了一个正确解。
-fn find_solutions_for_permutation(perm: &[f64]) -> HashSet<String> {
-    let mut solutions = HashSet::new();
-    let ops = ['+', '-', '*', '/'];
-    for &op1 in &ops {
-        for &op2 in &ops {
-            for &op3 in &ops {
-                // For each structure, call small pure helpers and insert any match.
-                if let Some(s) = try_struct1(perm, op1, op2, op3) {
-                    solutions.insert(s);
-                }
-                if let Some(s) = try_struct2(perm, op1, op2, op3) {
-                    solutions.insert(s);
-                }
-                if let Some(s) = try_struct3(perm, op1, op2, op3) {
-                    solutions.insert(s);
-                }
-                if let Some(s) = try_struct4(perm, op1, op2, op3) {
-                    solutions.insert(s);
-                }
-                if let Some(s) = try_struct5(perm, op1, op2, op3) {
-                    solutions.insert(s);
-                }
+/// 这里的流程是：
+/// 1. 打开（或创建）日志文件并定位到末尾；
+/// 2. 用随机（或 `--seed` 给出的）种子抽取 4 张牌；
+/// 3. 按 `solver.toml`（若存在）中的配置求出所有表达式；
+/// 4. 按时间戳记录抽到的牌、种子和对应的所有解，若无解则写入提示；
+/// 5. 给出 `--count N` 时重复第 2~4 步，共处理 `N` 手牌。
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = parse_args(&args).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    let config = solver_config();
+    if let Some(path) = &options.verify {
+        std::process::exit(verify(path, &config));
+    }
+
+    // Ensure the `log` directory exists so opening the file won't fail.
+    std::fs::create_dir_all("log").expect("Failed to create log directory");
+
+    let mut log_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(options.format.log_path())
+        .expect("Failed to open log file");
+    // Run the requested hands (generated or taken from the command line, solved, logged)
+    // and then exit.
+    let mut stats = SessionStats::new();
+    let mut times = Vec::new();
+    for i in 0..options.count.unwrap_or(1) {
+        let (hand, seed) = match &options.cards {
+            Some(cards) => (cards.clone(), None),
+            None => {
+                let seed = match options.seed {
+                    Some(seed) => seed.wrapping_add(i as u64),
+                    None => thread_rng().gen(),
+                };
+                (hand_for_seed(seed), Some(seed))
+            }
+        };
+
+        let (full, elapsed) = timed(|| solve_with_config_full(&hand, &config));
+        let solutions: Vec<String> = full.iter().map(|s| s.text.clone()).collect();
+
+        let log_format = &config.log;
+        let now = Local::now().naive_local();
+        match options.format {
+            OutputFormat::Text => {
+                write!(
+                    log_file,
+                    "{}",
+                    log_format.entry(&now, &hand, seed, &solutions)
+                )
+                .unwrap();
+            }
+            OutputFormat::Jsonl => {
+                let record =
+                    JsonRecord::new(log_format.timestamp(&now), &hand, &solutions).with_seed(seed);
+                writeln!(log_file, "{}", record.to_jsonl()).unwrap();
             }
         }
-    }
+        if options.oneline {
+            println!("{}", oneline_summary(&hand, &full));
+        } else {
+            println!(
+                "Processed hand: {:3?}.\t Solution count {:12}.",
+                hand,
+                solutions.len()
+            );
+        }
 
-    solutions
-}
+        if options.time {
+            println!("{}", timing_line(elapsed));
+        }
+        times.push(elapsed);
+        stats.record(&hand, &solutions);
+    }
 
-// Each of the following functions represents one of the five parenthesization
-// structures. They are pure (no mutation) and return an Option<String>
-// describing the expression when it evaluates to TARGET.
-fn try_struct1(perm: &[f64], op1: char, op2: char, op3: char) -> Option<String> {
-    // (a op1 b) op2 (c op3 d)
-    let first = apply_op(perm[0], perm[1], op1).unwrap_or_else(|| f64::NAN);
-    let second = apply_op(perm[2], perm[3], op3).unwrap_or_else(|| f64::NAN);
-    let result = apply_op(first, second, op2).unwrap_or_else(|| f64::NAN);
-    if (result - TARGET).abs() < EPSILON {
-        Some(format!(
-            "({} {} {}) {} ({} {} {})",
-            perm[0], op1, perm[1], op2, perm[2], op3, perm[3]
-        ))
-    } else {
-        None
+    if options.time && times.len() > 1 {
+        println!("{}", timing_totals(&times));
+    }
+    if let Some(path) = &options.summary {
+        stats
+            .write_summary(path)
+            .expect("Failed to write session summary");
     }
+
+    // println!("Log file has been updated.");
 }
 
-fn try_struct2(perm: &[f64], op1: char, op2: char, op3: char) -> Option<String> {
-    // ((a op1 b) op2 c) op3 d
-    let first = apply_op(perm[0], perm[1], op1).unwrap_or_else(|| f64::NAN);
-    let second = apply_op(first, perm[2], op2).unwrap_or_else(|| f64::NAN);
-    let result = apply_op(second, perm[3], op3).unwrap_or_else(|| f64::NAN);
-    if (result - TARGET).abs() < EPSILON {
-        Some(format!(
-            "(({} {} {}) {} {}) {} {}",
-            perm[0], op1, perm[1], op2, perm[2], op3, perm[3]
-        ))
+/// 读取 `solver.toml`（若存在）中的配置，否则使用标准规则；配置文件不合法时退出。
+fn solver_config() -> SolverConfig {
+    if std::path::Path::new(CONFIG_PATH).exists() {
+        load_config(CONFIG_PATH).unwrap_or_else(|e| {
+            eprintln!("{}: {}", CONFIG_PATH, e);
+            std::process::exit(2);
+        })
     } else {
-        None
+        SolverConfig::default()
     }
 }
 
-fn try_struct3(perm: &[f64], op1: char, op2: char, op3: char) -> Option<String> {
-    // a op1 (b op2 (c op3 d))
-    let first = apply_op(perm[2], perm[3], op3).unwrap_or_else(|| f64::NAN);
-    let second = apply_op(perm[1], first, op2).unwrap_or_else(|| f64::NAN);
-    let result = apply_op(perm[0], second, op1).unwrap_or_else(|| f64::NAN);
-    if (result - TARGET).abs() < EPSILON {
-        Some(format!(
-            "{} {} ({} {} ({} {} {}))",
-            perm[0], op1, perm[1], op2, perm[2], op3, perm[3]
-        ))
-    } else {
-        None
-    }
+/// 运行 `f` 并返回其结果与耗时。
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed())
 }
 
-fn try_struct4(perm: &[f64], op1: char, op2: char, op3: char) -> Option<String> {
-    // (a op1 (b op2 c)) op3 d
-    let first = apply_op(perm[1], perm[2], op2).unwrap_or_else(|| f64::NAN);
-    let second = apply_op(perm[0], first, op1).unwrap_or_else(|| f64::NAN);
-    let result = apply_op(second, perm[3], op3).unwrap_or_else(|| f64::NAN);
-    if (result - TARGET).abs() < EPSILON {
-        Some(format!(
-            "({} {} ({} {} {})) {} {}",
-            perm[0], op1, perm[1], op2, perm[2], op3, perm[3]
-        ))
-    } else {
-        None
-    }
+/// `--time` 打印的耗时行，例如 `Solve time: 0.412 ms`。
+fn timing_line(elapsed: Duration) -> String {
+    format!("Solve time: {:.3} ms", elapsed.as_secs_f64() * 1000.0)
 }
-fn try_struct5(perm: &[f64], op1: char, op2: char, op3: char) -> Option<String> {
-    // a op1 ((b op2 c) op3 d)
-    let first = apply_op(perm[1], perm[2], op2).unwrap_or_else(|| f64::NAN);
-    let second = apply_op(first, perm[3], op3).unwrap_or_else(|| f64::NAN);
-    let result = apply_op(perm[0], second, op1).unwrap_or_else(|| f64::NAN);
-    if (result - TARGET).abs() < EPSILON {
-        Some(format!(
-            "{} {} (({} {} {}) {} {})",
-            perm[0], op1, perm[1], op2, perm[2], op3, perm[3]
-        ))
-    } else {
-        None
-    }
+
+/// 处理多手牌时 `--time` 最后打印的汇总行，例如
+/// `Total solve time: 3.000 ms over 2 hands (average 1.500 ms)`。
+fn timing_totals(times: &[Duration]) -> String {
+    let total: Duration = times.iter().sum();
+    let total_ms = total.as_secs_f64() * 1000.0;
+    format!(
+        "Total solve time: {:.3} ms over {} hands (average {:.3} ms)",
+        total_ms,
+        times.len(),
+        total_ms / times.len().max(1) as f64
+    )
 }
 
-/// 尝试对两个操作数应用运算符，必要时拦截非法操作并返回 `None`。
+/// `--oneline` 的摘要行，例如 `3 3 8 8 | solvable | 1 | 8/(3-(8/3))`。
 ///
-/// - 加、减、乘总是有效；
-/// - 除法在分母绝对值小于 `EPSILON` 时直接跳过，以避免除零和数值震荡；
-/// - `None` 会在上层被忽略，从而保证算法的健壮性。
-fn apply_op(a: f64, b: f64, op: char) -> Option<f64> {
-    match op {
-        '+' => Some(a + b),
-        '-' => Some(a - b),
-        '*' => Some(a * b),
-        '/' if b.abs() > EPSILON => Some(a / b),
-        _ => None,
-    }
+/// `solutions` 是这手牌已经按 `solver.toml` 求出的解，与日志中写入的一致；
+/// 最简解按 `best_of` 的标准挑选，去掉空格以免被按空白切分，无解时写 `-`。
+fn oneline_summary(hand: &[i32], solutions: &[Solution]) -> String {
+    let cards: Vec<String> = hand.iter().map(|c| c.to_string()).collect();
+    let best = best_of(solutions).map_or("-".to_string(), |s| s.text.replace(' ', ""));
+    let count = solutions.len();
+    let status = if count > 0 { "solvable" } else { "unsolvable" };
+    format!("{} | {} | {} | {}", cards.join(" "), status, count, best)
 }
 
-#[cfg(test)]
-mod test_vec {
-    #[test]
-    fn arr_basic() {
-        let arr = [10, 20, 30];
-        assert_eq!(arr[0], 10);
-        assert_eq!(arr[1], 20);
-        assert_eq!(arr[2], 30);
-        assert_eq!(arr.len(), 3);
-
-        let arr2 = [&arr[..], &arr[1..=2]];
-
-        println!("arr2: {:?}", arr2);
-
-        let v = vec![10, 20, 30];
-        assert_eq!(v[0], 10);
-        assert_eq!(v[1], 20);
-        assert_eq!(v[2], 30);
-        assert_eq!(v.len(), 3);
-
-        let mut v2 = Vec::new();
-        v2.push(100);
-        v2.push(200);
-        assert_eq!(v2.len(), 2);
-        assert_eq!(v2[0], 100);
-        assert_eq!(v2[1], 200);
+/// 按 `config` 校验日志文件，逐条打印发现的问题；返回进程退出码（有问题时为 1）。
+fn verify(path: &str, config: &SolverConfig) -> i32 {
+    let content = std::fs::read_to_string(path).expect("Failed to read log file");
+    let discrepancies = verify_log_with(&content, config);
+    for d in &discrepancies {
+        println!("{}", d);
     }
-
-    #[test]
-    fn test_array_range_collect() {
-        assert_eq!((3..=5), std::ops::RangeInclusive::new(3, 5));
-        assert_eq!((1..2), std::ops::Range { start: 1, end: 2 });
-        assert_eq!(3 + 4 + 5, (3..=5).sum());
-        let arr = [0, 1, 2, 3, 4];
-        assert_eq!(arr[..], [0, 1, 2, 3, 4]);
-        assert_eq!(arr[..3], [0, 1, 2]);
-        assert_eq!(arr[..=3], [0, 1, 2, 3]);
-        assert_eq!(arr[1..], [1, 2, 3, 4]);
-        assert_eq!(arr[1..3], [1, 2]);
-        assert_eq!(arr[1..=3], [1, 2, 3]); // This is a `RangeInclusive`
+    if discrepancies.is_empty() {
+        println!("{}: all entries match the current solver.", path);
+        0
+    } else {
+        println!("{}: {} discrepancies found.", path, discrepancies.len());
+        1
     }
 }
 
@@ -303,80 +343,149 @@ mod test_vec {
 mod tests {
     use super::*;
 
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
     #[test]
-    fn test_apply_op_basic() {
-        assert_eq!(apply_op(2.0, 3.0, '+'), Some(5.0));
-        assert_eq!(apply_op(5.0, 3.0, '-'), Some(2.0));
-        assert_eq!(apply_op(4.0, 3.0, '*'), Some(12.0));
-        assert_eq!(apply_op(8.0, 2.0, '/'), Some(4.0));
-        // division by (near) zero should return None
-        assert_eq!(apply_op(1.0, 1e-9, '/'), None);
+    fn test_parse_args_verify() {
+        assert_eq!(parse_args(&[]), Ok(Options::default()));
+        let options = parse_args(&args(&["--verify", "log/24_game_log.txt"])).unwrap();
+        assert_eq!(options.verify.as_deref(), Some("log/24_game_log.txt"));
+        assert!(parse_args(&args(&["--verify"])).is_err());
+        assert!(parse_args(&args(&["--bogus"])).is_err());
     }
 
     #[test]
-    fn test_permutations_count() {
-        let nums = vec![1.0, 2.0, 3.0, 4.0];
-        let perms = permutations(&nums);
-        println!("Generated permutations: {:?}", perms);
-        assert_eq!(perms.len(), 24); // 4! = 24
-        let unique_perms: HashSet<_> = perms
-            .into_iter()
-            .map(|p| p.iter().map(|&f| f.to_bits()).collect::<Vec<u64>>())
-            .collect();
-        assert_eq!(unique_perms.len(), 24); // all should be unique
+    fn test_seed_replays_hand() {
+        let options = parse_args(&args(&["--seed", "12345"])).unwrap();
+        assert_eq!(options.seed, Some(12345));
+        assert!(parse_args(&args(&["--seed", "abc"])).is_err());
+        assert!(parse_args(&args(&["--seed"])).is_err());
+
+        // The seed written to the log is enough to draw the same hand again.
+        let logged = hand_for_seed(12345);
+        assert_eq!(hand_for_seed(12345), logged);
+        assert_eq!(logged.len(), 4);
     }
 
     #[test]
-    fn test_permutations_count_repeated() {
-        let nums = vec![1.0, 2.0, 2.0];
-        let perms = permutations(&nums);
-        print!("Generated permutations with repeats: {:?}", perms);
-        assert_eq!(perms.len(), 6); // 3! / 2! = 3
-        let unique_perms: HashSet<_> = perms
-            .into_iter()
-            .map(|p| p.iter().map(|&f| f.to_bits()).collect::<Vec<u64>>())
-            .collect();
-        assert_eq!(unique_perms.len(), 3); // only 3 unique
+    fn test_parse_hand() {
+        assert_eq!(
+            parse_hand(&args(&["3388"]), CardValueMap::Standard),
+            Ok(vec![3, 3, 8, 8])
+        );
+        assert_eq!(
+            parse_hand(&args(&["10 11 1 2"]), CardValueMap::Standard),
+            Ok(vec![10, 11, 1, 2])
+        );
+        assert_eq!(
+            parse_hand(&args(&["3", "3", "8", "8"]), CardValueMap::Standard),
+            Ok(vec![3, 3, 8, 8])
+        );
+        // Digits outside 1..=9 are not split, so `1011` is one out-of-range card.
+        assert!(parse_hand(&args(&["1011"]), CardValueMap::Standard).is_err());
+        assert_eq!(
+            parse_hand(&args(&["3", "3", "8"]), CardValueMap::Standard),
+            Err("expected 4 cards, got 3".to_string())
+        );
+        assert!(parse_hand(&args(&["1 2 3 4 5"]), CardValueMap::Standard).is_err());
+        assert_eq!(
+            parse_hand(&args(&["0", "3", "8", "8"]), CardValueMap::Standard),
+            Err("card 0 is not in 1..=13".to_string())
+        );
+        assert!(parse_hand(&args(&["14 3 8 8"]), CardValueMap::Standard).is_err());
+        assert!(parse_args(&args(&["3", "3", "8"])).is_err());
+        assert!(parse_hand(&args(&["3", "x"]), CardValueMap::Standard).is_err());
+        assert_eq!(
+            parse_hand(&args(&["K K 2 2"]), CardValueMap::Blackjack),
+            Ok(vec![10, 10, 2, 2])
+        );
+        // A number is a rank too, so 13 counts as 10 just like K.
+        assert_eq!(
+            parse_hand(&args(&["13 K 11 2"]), CardValueMap::Blackjack),
+            Ok(vec![10, 10, 10, 2])
+        );
+        assert_eq!(
+            parse_hand(&args(&["14 3 8 8"]), CardValueMap::Blackjack),
+            Err("card 14 is not in 1..=13".to_string())
+        );
+
+        let options = parse_args(&args(&["--oneline", "3388"])).unwrap();
+        assert_eq!(options.cards, Some(vec![3, 3, 8, 8]));
+
+        let options = parse_args(&args(&["--faces", "blackjack", "Q", "A", "2", "3"])).unwrap();
+        assert_eq!(options.cards, Some(vec![10, 1, 2, 3]));
+        assert!(parse_args(&args(&["--faces", "euro"])).is_err());
     }
 
     #[test]
-    fn test_try_struct1_success_and_failure() {
-        let perm = [6.0, 2.0, 3.0, 4.0];
-        // (6 * 2) + (3 * 4) == 24
-        assert!(try_struct1(&perm, '*', '+', '*').is_some());
-        println!(
-            "Found expression: {}",
-            try_struct1(&perm, '*', '+', '*').unwrap()
+    fn test_oneline_summary() {
+        assert!(parse_args(&args(&["--oneline"])).unwrap().oneline);
+        let summary = |hand: &[i32], config: &SolverConfig| {
+            oneline_summary(hand, &solve_with_config_full(hand, config))
+        };
+        let standard = SolverConfig::default();
+        assert_eq!(
+            summary(&[3, 3, 8, 8], &standard),
+            "3 3 8 8 | solvable | 1 | 8/(3-(8/3))"
+        );
+        assert_eq!(
+            summary(&[1, 1, 1, 1], &standard),
+            "1 1 1 1 | unsolvable | 0 | -"
+        );
+        // The summary reflects the configured rules, not the standard ones.
+        let no_division = SolverConfig {
+            ops: vec!['+', '-', '*'],
+            ..SolverConfig::default()
+        };
+        assert_eq!(
+            summary(&[3, 3, 8, 8], &no_division),
+            "3 3 8 8 | unsolvable | 0 | -"
         );
-        // wrong ops shouldn't match
-        assert!(try_struct1(&perm, '+', '+', '+').is_none());
     }
 
     #[test]
-    fn test_try_struct2_success() {
-        let perm = [2.0, 3.0, 4.0, 1.0];
-        // ((2 * 3) * 4) * 1 == 24
-        assert!(try_struct2(&perm, '*', '*', '*').is_some());
+    fn test_parse_args_format() {
+        assert_eq!(parse_args(&[]).unwrap().format, OutputFormat::Text);
+        let options = parse_args(&args(&["--format", "jsonl"])).unwrap();
+        assert_eq!(options.format, OutputFormat::Jsonl);
+        assert!(parse_args(&args(&["--format", "xml"])).is_err());
+        assert!(parse_args(&args(&["--format"])).is_err());
     }
 
     #[test]
-    fn test_try_struct3_success() {
-        let perm = [3.0, 2.0, 4.0, 1.0];
-        // 3 * (2 * (4 * 1)) == 24
-        assert!(try_struct3(&perm, '*', '*', '*').is_some());
+    fn test_timing() {
+        assert!(parse_args(&args(&["--time"])).unwrap().time);
+        let (solutions, elapsed) =
+            timed(|| solve_with_config_full(&[3, 3, 8, 8], &SolverConfig::default()));
+        assert_eq!(solutions.len(), 1);
+        assert!(elapsed > Duration::ZERO && elapsed < Duration::from_secs(10));
+        assert_eq!(
+            timing_line(Duration::from_micros(1500)),
+            "Solve time: 1.500 ms"
+        );
+        assert_eq!(
+            timing_totals(&[Duration::from_micros(1000), Duration::from_micros(2000)]),
+            "Total solve time: 3.000 ms over 2 hands (average 1.500 ms)"
+        );
     }
 
     #[test]
-    fn test_try_struct4_success() {
-        let perm = [2.0, 3.0, 4.0, 1.0];
-        // (2 * (3 * 4)) * 1 == 24
-        assert!(try_struct4(&perm, '*', '*', '*').is_some());
+    fn test_parse_args_count() {
+        assert_eq!(parse_args(&[]).unwrap().count, None);
+        let options = parse_args(&args(&["--count", "5", "--time"])).unwrap();
+        assert_eq!(options.count, Some(5));
+        assert!(parse_args(&args(&["--count", "0"])).is_err());
+        assert!(parse_args(&args(&["--count", "x"])).is_err());
+        assert!(parse_args(&args(&["--count"])).is_err());
+        assert!(parse_args(&args(&["--count", "2", "3388"])).is_err());
     }
 
     #[test]
-    fn test_try_struct5_success() {
-        let perm = [3.0, 2.0, 2.0, 2.0];
-        // 3 * ((2 * 2) * 2) == 24
-        assert!(try_struct5(&perm, '*', '*', '*').is_some());
+    fn test_parse_args_summary() {
+        let options = parse_args(&args(&["--summary", "out.txt"])).unwrap();
+        assert_eq!(options.summary.as_deref(), Some("out.txt"));
+        assert!(parse_args(&args(&["--summary"])).is_err());
     }
 }
@@ -0,0 +1,200 @@
+//! 精确的有理数运算。
+//!
+//! 求解器使用 `f64` 加 `EPSILON` 比较，这对 1~13 的四张牌足够可靠；
+//! 但在统计中间结果（例如 `8 / 3`）时，需要精确地区分整数与分数，
+//! 这里提供一个基于 `i64` 的最简分数类型，以及对 `Expr` 的精确求值。
+
+use crate::expr::Expr;
+use crate::{eval_struct_exact, solve_by};
+use std::fmt;
+
+/// 最简分数：分母恒为正，且分子分母互素。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i64,
+    den: i64,
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Rational {
+    /// 构造 `num / den` 并约分；分母为 0 时返回 `None`。
+    pub fn new(num: i64, den: i64) -> Option<Rational> {
+        if den == 0 {
+            return None;
+        }
+        let g = gcd(num, den);
+        let sign = if den < 0 { -1 } else { 1 };
+        Some(Rational {
+            num: sign * num / g,
+            den: sign * den / g,
+        })
+    }
+
+    /// 整数 `n` 对应的分数 `n / 1`。
+    pub fn from_int(n: i64) -> Rational {
+        Rational { num: n, den: 1 }
+    }
+
+    pub fn numer(&self) -> i64 {
+        self.num
+    }
+
+    pub fn denom(&self) -> i64 {
+        self.den
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    /// 绝对值；分子为 `i64::MIN` 时溢出，返回 `None`。
+    pub fn abs(self) -> Option<Rational> {
+        Some(Rational {
+            num: self.num.checked_abs()?,
+            den: self.den,
+        })
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// 与 `apply_op` 对应的精确运算：除以 0、未知运算符或 `i64` 溢出时返回 `None`。
+    pub fn apply(self, other: Rational, op: char) -> Option<Rational> {
+        let (a, b, c, d) = (self.num, self.den, other.num, other.den);
+        match op {
+            '+' => Rational::new(
+                a.checked_mul(d)?.checked_add(c.checked_mul(b)?)?,
+                b.checked_mul(d)?,
+            ),
+            '-' => Rational::new(
+                a.checked_mul(d)?.checked_sub(c.checked_mul(b)?)?,
+                b.checked_mul(d)?,
+            ),
+            '*' => Rational::new(a.checked_mul(c)?, b.checked_mul(d)?),
+            '/' => Rational::new(a.checked_mul(d)?, b.checked_mul(c)?),
+            _ => None,
+        }
+    }
+}
+
+impl Ord for Rational {
+    // Denominators are positive, so cross-multiplying preserves the order.
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.num as i128 * other.den as i128).cmp(&(other.num as i128 * self.den as i128))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+// Leaves come from integer cards; a non-integer leaf has no exact meaning here.
+fn leaf_value(v: f64) -> Option<Rational> {
+    (v.fract() == 0.0 && v.abs() < i64::MAX as f64).then(|| Rational::from_int(v as i64))
+}
+
+/// 对表达式树做精确的有理数求值，叶子必须是整数。
+pub fn eval_exact(expr: &Expr) -> Option<Rational> {
+    match expr {
+        Expr::Num(v) => leaf_value(*v),
+        Expr::Neg(inner) => Rational::from_int(0).apply(eval_exact(inner)?, '-'),
+        Expr::Abs(inner) => eval_exact(inner)?.abs(),
+        Expr::Bin(op, lhs, rhs) => eval_exact(lhs)?.apply(eval_exact(rhs)?, *op),
+    }
+}
+
+/// 按后序（先子树后父节点）列出每个二元运算节点的精确值。
+///
+/// 对四张牌的解而言共有 3 个值，最后一个就是整个表达式的结果；
+/// 任何一步无法精确求值时返回 `None`。
+pub fn exact_intermediates(expr: &Expr) -> Option<Vec<Rational>> {
+    fn walk(expr: &Expr, out: &mut Vec<Rational>) -> Option<Rational> {
+        match expr {
+            Expr::Num(v) => leaf_value(*v),
+            Expr::Neg(inner) => Rational::from_int(0).apply(walk(inner, out)?, '-'),
+            Expr::Abs(inner) => walk(inner, out)?.abs(),
+            Expr::Bin(op, lhs, rhs) => {
+                let value = walk(lhs, out)?.apply(walk(rhs, out)?, *op)?;
+                out.push(value);
+                Some(value)
+            }
+        }
+    }
+
+    let mut values = Vec::new();
+    walk(expr, &mut values)?;
+    Some(values)
+}
+
+/// 用精确的有理数运算求 24，不受浮点误差影响。
+///
+/// 枚举方式与 `solve_24` 相同，只是每种结构都用 `eval_struct_exact` 求值，
+/// 结果必须恰好等于 24。
+pub fn solve_24_exact(cards: &[i32]) -> Vec<String> {
+    solve_by(cards, eval_struct_exact, |v| v == Rational::from_int(24))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::parse_expr;
+
+    #[test]
+    fn test_rational_normalizes() {
+        let r = Rational::new(6, -4).unwrap();
+        assert_eq!((r.numer(), r.denom()), (-3, 2));
+        assert_eq!(r.to_string(), "-3/2");
+        assert!(Rational::new(1, 0).is_none());
+        assert!(Rational::new(8, 4).unwrap().is_integer());
+        assert!(Rational::new(1, 3).unwrap() < Rational::new(1, 2).unwrap());
+        assert!(Rational::new(-5, 2).unwrap() < Rational::from_int(-2));
+    }
+
+    #[test]
+    fn test_eval_exact() {
+        let expr = parse_expr("8 / (3 - 8 / 3)").unwrap();
+        assert_eq!(eval_exact(&expr), Some(Rational::from_int(24)));
+        assert_eq!(eval_exact(&parse_expr("1 / (2 - 2)").unwrap()), None);
+
+        let steps = exact_intermediates(&expr).unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                Rational::new(8, 3).unwrap(),
+                Rational::new(1, 3).unwrap(),
+                Rational::from_int(24),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solve_24_exact() {
+        assert_eq!(solve_24_exact(&[3, 3, 8, 8]), vec!["8 / (3 - (8 / 3))"]);
+        let mut exact = solve_24_exact(&[6, 2, 3, 4]);
+        let mut float = crate::solve_24(&[6, 2, 3, 4]);
+        exact.sort();
+        float.sort();
+        assert_eq!(exact, float);
+        assert!(solve_24_exact(&[1, 1, 1, 1]).is_empty());
+    }
+}
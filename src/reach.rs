@@ -0,0 +1,217 @@
+//! 一手牌能算出哪些值。
+//!
+//! 与求 24 不同，这里不匹配单个目标，而是收集所有表达式树的最终结果，
+//! 用于"这手牌能凑出哪些数"之类的玩法。
+
+use crate::expr::Expr;
+use crate::rational::solve_24_exact;
+use crate::{apply_op, build_struct, eval_struct, op_triples, permutations, trees_for_sequence};
+use crate::{TreeShape, EPSILON, OPERATORS, TARGET};
+use std::collections::{BTreeSet, HashSet};
+use std::ops::RangeInclusive;
+
+// Every expression tree over every ordering of the cards, with its value.
+fn reachable_trees(cards: &[i32]) -> Vec<(f64, Expr)> {
+    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
+    permutations(&nums)
+        .iter()
+        .flat_map(|perm| trees_for_sequence(perm, &OPERATORS))
+        .collect()
+}
+
+fn reachable_values(cards: &[i32]) -> Vec<f64> {
+    reachable_trees(cards)
+        .into_iter()
+        .map(|(value, _)| value)
+        .collect()
+}
+
+/// 返回 `range` 中这手牌能恰好算出的所有整数。
+///
+/// 与 `solve_subset` 使用同一个通用枚举，每张牌恰好用一次；
+/// 结果与某个整数相差不到 `EPSILON` 即视为能算出该整数。
+/// 例如 `reachable_targets(&cards, 1..=100)` 对应"能凑出 1~100 中哪些数"。
+pub fn reachable_targets(cards: &[i32], range: RangeInclusive<i32>) -> BTreeSet<i32> {
+    reachable_values(cards)
+        .into_iter()
+        .filter(|v| (v - v.round()).abs() < EPSILON)
+        .map(|v| v.round())
+        .filter(|v| *v >= *range.start() as f64 && *v <= *range.end() as f64)
+        .map(|v| v as i32)
+        .collect()
+}
+
+/// 返回这手牌能算出的、不小于 `floor` 的最小值；所有结果都小于 `floor` 时返回 `None`。
+///
+/// 用于"尽量接近但不低于某个数"的计分规则。与 `floor` 相差不到 `EPSILON`
+/// 的结果视为恰好等于 `floor`，以免 `8 / 3` 这类值因浮点误差被漏掉。
+pub fn smallest_reachable_at_least(cards: &[i32], floor: f64) -> Option<f64> {
+    reachable_values(cards)
+        .into_iter()
+        .filter(|&v| v > floor - EPSILON)
+        .min_by(f64::total_cmp)
+}
+
+/// 列出结果与 `target` 相差不超过 `window` 的所有表达式及其误差。
+///
+/// 用于无解时给出"尽力而为"的答案：结果按误差从小到大排列，误差相同时按
+/// 表达式字典序；同一表达式字符串只出现一次。恰好等于 `target` 的解误差为 0（或
+/// 极小的浮点误差），同样会列出。
+pub fn near_misses(cards: &[i32], target: f64, window: f64) -> Vec<(String, f64)> {
+    let mut seen = HashSet::new();
+    let mut misses: Vec<(String, f64)> = reachable_trees(cards)
+        .into_iter()
+        .map(|(value, expr)| (expr.to_string(), (value - target).abs()))
+        .filter(|(text, error)| *error <= window && seen.insert(text.clone()))
+        .collect();
+    misses.sort_by(|(a, x), (b, y)| x.total_cmp(y).then_with(|| a.cmp(b)));
+    misses
+}
+
+/// 列出求解器能为这手牌构造的每个不同的表达式及其值，不做目标值检查。
+///
+/// 枚举方式与 `solve_24` 相同：每个排列有 4^3 种运算符组合乘以 5 种括号形态，
+/// 即 320 个表达式，4 张牌共 24 * 320 = 7680 个。除数接近 0 而无法求值的表达式
+/// 不列出；牌中有重复点数时相同的字符串只保留第一次出现的那个，其余按枚举顺序排列。
+/// 结果很大，只适合用于探索单手牌，不要对全部牌型调用。
+pub fn all_expressions(cards: &[i32]) -> Vec<(String, f64)> {
+    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
+    let mut seen = HashSet::new();
+    let mut expressions = Vec::new();
+    for perm in permutations(&nums) {
+        for ops in op_triples(&OPERATORS) {
+            for shape in TreeShape::ALL {
+                if let Some(value) = eval_struct(shape, &perm, ops) {
+                    let text = build_struct(shape, &perm, ops).to_string();
+                    if seen.insert(text.clone()) {
+                        expressions.push((text, value));
+                    }
+                }
+            }
+        }
+    }
+    expressions
+}
+
+/// `minimal_epsilon` 考虑的最大容差：容差达到 1 时连相邻的整数都会被当作 24，
+/// 已经不是"浮点误差"意义上的容差了。
+pub const MAX_MINIMAL_EPSILON: f64 = 1.0;
+
+/// 返回让这手牌至少有一个解被接受所需的最小容差，用来衡量一手牌在数值上有多"勉强"。
+///
+/// 用有理数精确计算能得到 24（`solve_24_exact` 有解）时返回 `Some(0.0)`；
+/// 否则返回 `all_expressions` 中结果与 24 的最小误差。最小误差不小于
+/// `MAX_MINIMAL_EPSILON` 时没有表达式足够接近，返回 `None`。
+pub fn minimal_epsilon(cards: &[i32]) -> Option<f64> {
+    if !solve_24_exact(cards).is_empty() {
+        return Some(0.0);
+    }
+    all_expressions(cards)
+        .into_iter()
+        .map(|(_, value)| (value - TARGET).abs())
+        .min_by(f64::total_cmp)
+        .filter(|&error| error < MAX_MINIMAL_EPSILON)
+}
+
+/// 逐张出牌的玩法中，判断当前累计值 `accumulator` 再依次用完 `remaining`
+/// 中的每张牌后，能否恰好得到 `target`。
+///
+/// 每出一张牌就把累计值更新为 `accumulator op card`：累计值始终是左操作数，
+/// 搜索的是剩余牌的出牌顺序与每一步的运算符，不会把几张剩余牌先组合起来。
+/// 没有剩余牌时只比较累计值本身；与目标相差不到 `EPSILON` 即视为达到。
+pub fn can_reach(accumulator: f64, remaining: &[i32], target: f64) -> bool {
+    if remaining.is_empty() {
+        return (accumulator - target).abs() < EPSILON;
+    }
+    (0..remaining.len()).any(|i| {
+        let mut rest = remaining.to_vec();
+        let card = rest.remove(i) as f64;
+        OPERATORS.iter().any(|&op| {
+            apply_op(accumulator, card, op).is_some_and(|next| can_reach(next, &rest, target))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reachable_targets() {
+        let targets = reachable_targets(&[1, 2, 3, 4], 0..=100);
+        assert!(targets.contains(&24));
+        // (1 + 2 - 3) * 4, (4 - 3) * (2 - 1), 1 + 2 + 3 + 4 and (1 + 2) * 3 * 4.
+        for n in [0, 1, 10, 36] {
+            assert!(targets.contains(&n), "{}", n);
+        }
+        // (1 + 2) * 3 * 4 is the largest value this hand can reach.
+        assert_eq!(targets.last(), Some(&36));
+        assert!(reachable_targets(&[1, 1, 1, 1], 0..=100)
+            .iter()
+            .all(|&n| n <= 4));
+        assert!(reachable_targets(&[1, 2, 3, 4], 200..=300).is_empty());
+    }
+
+    #[test]
+    fn test_smallest_reachable_at_least() {
+        // Four 1s reach small values such as 1/2, 1, 3/2, 2 and 3, but nothing above 4.
+        let cards = [1, 1, 1, 1];
+        assert_eq!(smallest_reachable_at_least(&cards, 2.5), Some(3.0));
+        assert_eq!(smallest_reachable_at_least(&cards, 0.4), Some(0.5));
+        assert_eq!(smallest_reachable_at_least(&cards, 4.0), Some(4.0));
+        assert_eq!(smallest_reachable_at_least(&cards, 4.5), None);
+        // (2 + 2) * (2 + 3) hits the floor exactly.
+        assert_eq!(smallest_reachable_at_least(&[2, 2, 2, 3], 20.0), Some(20.0));
+    }
+
+    #[test]
+    fn test_near_misses() {
+        // Four 1s top out at 4, so nothing is within 19 of 24.
+        assert!(near_misses(&[1, 1, 1, 1], 24.0, 19.0).is_empty());
+        let misses = near_misses(&[1, 1, 1, 1], 24.0, 21.0);
+        assert_eq!(misses[0].1, 20.0);
+        assert!(misses
+            .iter()
+            .all(|(_, error)| *error >= misses[0].1 && *error <= 21.0));
+        assert!(misses.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(misses.iter().any(|(s, _)| s == "(1 + 1) * (1 + 1)"));
+    }
+
+    #[test]
+    fn test_can_reach() {
+        // 3 played so far: (3 + 5) * 3, or 3 * 8 with nothing left.
+        assert!(can_reach(3.0, &[5, 3], 24.0));
+        assert!(can_reach(3.0, &[8], 24.0));
+        assert!(can_reach(24.0, &[], 24.0));
+        // 1 then 1 and 1 can at most reach 3.
+        assert!(!can_reach(1.0, &[1, 1], 24.0));
+        // 8 / (3 - 8 / 3) needs the 3s combined first, which a running total cannot do.
+        assert!(!can_reach(8.0, &[3, 3, 8], 24.0));
+    }
+
+    #[test]
+    fn test_all_expressions() {
+        // No subset of these cards evaluates to 0, so nothing divides by zero and
+        // every one of the 24 * 320 expressions is kept.
+        let expressions = all_expressions(&[5, 7, 11, 13]);
+        assert_eq!(expressions.len(), 24 * 320);
+        assert!(expressions.contains(&("((5 + 7) + 11) + 13".to_string(), 36.0)));
+        // Duplicate cards collapse identical strings, and 4 / (3 - (1 + 2)) is dropped.
+        assert!(all_expressions(&[3, 3, 8, 8]).len() < 24 * 320);
+        assert!(all_expressions(&[1, 2, 3, 4])
+            .iter()
+            .all(|(s, _)| s != "4 / (3 - (1 + 2))"));
+    }
+
+    #[test]
+    fn test_minimal_epsilon() {
+        // 8 / (3 - 8 / 3) carries a floating-point error but is exactly 24.
+        assert_eq!(minimal_epsilon(&[3, 3, 8, 8]), Some(0.0));
+        assert_eq!(minimal_epsilon(&[6, 2, 3, 4]), Some(0.0));
+        // Four 1s reach at most 4.
+        assert_eq!(minimal_epsilon(&[1, 1, 1, 1]), None);
+        // [1, 1, 7, 7] is unsolvable; (7 * 7) / (1 + 1) = 24.5 is the closest.
+        let error = minimal_epsilon(&[1, 1, 7, 7]).unwrap();
+        assert!((error - 0.5).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,554 @@
+//! 结构化的解。
+//!
+//! `solve_24` 只返回表达式字符串，调用方经常需要再解析一遍才能知道根运算符、
+//! 深度或是否用到除法；`Solution` 把这些信息一次性算好。
+
+use crate::answer::leaves;
+use crate::config::SolverConfig;
+use crate::expr::{canonical, eval, to_rpn, Expr};
+use crate::trees_for_atoms;
+use crate::{build_struct, eval_struct, op_triples, permutations, solve_24};
+use crate::{solve_subset_full, solve_with_config_full};
+use crate::{TreeShape, EPSILON, OPERATORS, TARGET};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::ControlFlow;
+
+/// 一个解：表达式树、对应的字符串，以及常用的派生信息。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    pub expr: Expr,
+    pub text: String,
+    pub uses_division: bool,
+    pub depth: usize,
+}
+
+impl Solution {
+    /// 由表达式树构造 `Solution`，`text` 即树的 `Display` 输出。
+    pub fn from_expr(expr: Expr) -> Solution {
+        Solution {
+            text: expr.to_string(),
+            uses_division: expr.operators().contains(&'/'),
+            depth: expr.depth(),
+            expr,
+        }
+    }
+}
+
+/// 与 `solve_24` 相同，但返回结构化的 `Solution`（见 `solve_with_config_full`）。
+pub fn solve_24_full(cards: &[i32]) -> Vec<Solution> {
+    solve_with_config_full(cards, &SolverConfig::default())
+}
+
+/// 与 `solve_24` 相同，但每个解都以逆波兰表示返回，便于对接计算器。
+pub fn solve_24_rpn(cards: &[i32]) -> Vec<String> {
+    solve_24_full(cards)
+        .iter()
+        .map(|s| to_rpn(&s.expr))
+        .collect()
+}
+
+/// 惰性地逐个产出一手牌的解对应的表达式树。
+///
+/// 与 `solve_24` 不同，这里不做去重，也不格式化字符串：每个命中 24 的
+/// （排列, 运算符, 括号形态）组合都会产出一棵树，牌中有重复点数时同一表达式
+/// 可能出现多次。调用方可以先按结构筛选，只对保留下来的树调用 `to_string()`。
+pub fn solution_trees_iter(cards: &[i32]) -> impl Iterator<Item = Expr> {
+    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
+    permutations(&nums).into_iter().flat_map(|perm| {
+        op_triples(&OPERATORS).into_iter().flat_map(move |ops| {
+            let perm = perm.clone();
+            TreeShape::ALL.into_iter().filter_map(move |shape| {
+                let value = eval_struct(shape, &perm, ops)?;
+                ((value - TARGET).abs() < EPSILON).then(|| build_struct(shape, &perm, ops))
+            })
+        })
+    })
+}
+
+/// 惰性求解时枚举（排列, 运算符, 括号形态）组合的顺序，决定哪些解先被产出。
+///
+/// 各种顺序枚举的组合完全相同，只是先后不同，因此完整枚举得到的解集不变。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EnumOrder {
+    /// 排列在最外层，其次是运算符组合，括号形态在最内层；与 `solve_24`、
+    /// `solution_trees_iter` 的顺序相同，这是默认值。
+    #[default]
+    ByPermutation,
+    /// 括号形态在最外层（按 `TreeShape::ALL` 的顺序），同一形态的解排在一起，
+    /// 形态内部仍按 `ByPermutation` 的顺序。
+    ByStructure,
+    /// 用到的运算符种类越少越靠前，例如 `((6 + 6) + 6) + 6` 先于
+    /// `(6 * 6) - (6 + 6)`；种类数相同时按 `ByPermutation` 的顺序。
+    ByOperatorCount,
+    /// 最简单的解优先：深度为 2 的 `Balanced` 形态排在最前，其次按运算符种类数、
+    /// 除法个数从少到多排列，其余按 `ByPermutation` 的顺序。
+    SimplestFirst,
+}
+
+fn distinct_ops(ops: [char; 3]) -> usize {
+    ops.iter().collect::<HashSet<_>>().len()
+}
+
+/// 与 `solution_trees_iter` 相同，但按 `order` 指定的顺序产出解。
+///
+/// 除 `ByPermutation` 外，需要先把全部 24 * 64 * 5 个组合排好序，
+/// 但求值仍然是惰性的：取够所需的解后，剩余的组合不会被计算。
+pub fn solution_trees_ordered(cards: &[i32], order: EnumOrder) -> impl Iterator<Item = Expr> {
+    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
+    let perms = permutations(&nums);
+    let mut combos = Vec::new();
+    for p in 0..perms.len() {
+        for ops in op_triples(&OPERATORS) {
+            for shape in TreeShape::ALL {
+                combos.push((p, ops, shape));
+            }
+        }
+    }
+    // All sorts are stable, so ties keep the by-permutation order.
+    match order {
+        EnumOrder::ByPermutation => {}
+        EnumOrder::ByStructure => combos.sort_by_key(|&(_, _, shape)| shape),
+        EnumOrder::ByOperatorCount => combos.sort_by_key(|&(_, ops, _)| distinct_ops(ops)),
+        EnumOrder::SimplestFirst => combos.sort_by_key(|&(_, ops, shape)| {
+            let divisions = ops.iter().filter(|&&op| op == '/').count();
+            (shape != TreeShape::Balanced, distinct_ops(ops), divisions)
+        }),
+    }
+    combos.into_iter().filter_map(move |(p, ops, shape)| {
+        let value = eval_struct(shape, &perms[p], ops)?;
+        ((value - TARGET).abs() < EPSILON).then(|| build_struct(shape, &perms[p], ops))
+    })
+}
+
+/// 最多返回 `max` 个不同的解，找够后立即停止枚举。
+///
+/// 解按 `solve_24` 的枚举顺序（`EnumOrder::ByPermutation`）返回；对解很多的牌，
+/// 比先求出全部解再截断要快得多。
+pub fn solve_limited(cards: &[i32], max: usize) -> Vec<String> {
+    solve_limited_ordered(cards, max, EnumOrder::ByPermutation)
+}
+
+/// 与 `solve_limited` 相同，但按 `order` 的顺序挑选前 `max` 个解，
+/// 例如 `EnumOrder::SimplestFirst` 会优先返回浅而简单的解。
+pub fn solve_limited_ordered(cards: &[i32], max: usize, order: EnumOrder) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut solutions = Vec::new();
+    for expr in solution_trees_ordered(cards, order) {
+        if solutions.len() >= max {
+            break;
+        }
+        let text = expr.to_string();
+        if seen.insert(text.clone()) {
+            solutions.push(text);
+        }
+    }
+    solutions
+}
+
+/// 以"推"的方式逐个交付解：每找到一个新解就调用一次 `on_solution`。
+///
+/// 回调内部做了去重，同一表达式字符串只会交付一次，因此全部交付完毕时
+/// 收集到的集合与 `solve_24` 相同。回调返回 `ControlFlow::Break(())` 即可提前结束
+/// 枚举，此时函数也返回 `Break`；完整枚举结束则返回 `Continue`。
+pub fn solve_24_callback(
+    cards: &[i32],
+    mut on_solution: impl FnMut(&str) -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    let mut seen = HashSet::new();
+    for expr in solution_trees_iter(cards) {
+        let text = expr.to_string();
+        if !seen.contains(&text) {
+            on_solution(&text)?;
+            seen.insert(text);
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+/// 解的去重策略。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dedup {
+    /// 不去重：每个命中目标的（排列, 运算符, 括号形态）组合都算一个解，
+    /// 牌中有重复点数时会出现完全相同的字符串。
+    None,
+    /// 按表达式字符串去重，即 `solve_24` 的行为。
+    Exact,
+    /// 按 `canonical` 规范形式去重，交换律等价的解只保留一个，返回规范形式的字符串。
+    Canonical,
+}
+
+/// 按指定的去重策略求解。
+///
+/// 同一手牌的解数满足 `Canonical <= Exact <= None`。
+pub fn solve_24_dedup(cards: &[i32], dedup: Dedup) -> Vec<String> {
+    match dedup {
+        Dedup::None => solution_trees_iter(cards).map(|e| e.to_string()).collect(),
+        Dedup::Exact => solve_24(cards),
+        Dedup::Canonical => solve_24_canonical(cards),
+    }
+}
+
+/// 按交换律去重后的解，按字典序排列。
+pub fn solve_24_canonical(cards: &[i32]) -> Vec<String> {
+    solve_24_canonical_trees(cards)
+        .iter()
+        .map(Expr::to_string)
+        .collect()
+}
+
+/// 与 `solve_24_canonical` 相同，但返回规范形式的表达式树，顺序也相同。
+pub fn solve_24_canonical_trees(cards: &[i32]) -> Vec<Expr> {
+    let mut trees: Vec<(String, Expr)> = solve_24_full(cards)
+        .iter()
+        .map(|s| {
+            let expr = canonical(&s.expr);
+            (expr.to_string(), expr)
+        })
+        .collect();
+    trees.sort_by(|a, b| a.0.cmp(&b.0));
+    trees.dedup_by(|a, b| a.0 == b.0);
+    trees.into_iter().map(|(_, expr)| expr).collect()
+}
+
+/// 惰性地逐手求解：每取一项才求解对应的那手牌，结果与输入顺序一致。
+///
+/// 适合处理很大的牌型文件：调用方只需把逐行读取的牌作为迭代器传入，
+/// 内存中始终只保留当前这一手的结果。每手牌的解与 `solve_24` 相同。
+pub fn solve_stream(
+    hands: impl Iterator<Item = Vec<i32>>,
+) -> impl Iterator<Item = (Vec<i32>, Vec<String>)> {
+    hands.map(|hand| {
+        let solutions = solve_24(&hand);
+        (hand, solutions)
+    })
+}
+
+/// 把已经写好的部分表达式 `prefix` 补全成 24，返回所有补全后的表达式树。
+///
+/// `prefix` 视为一个整体，作为补全结果中最左边的操作数，剩下的牌以任意顺序、
+/// 任意括号与运算符接在它后面；例如 `prefix` 为 `6` 时，`6 * (2 + (3 - 1))`
+/// 是 `[6, 2, 3, 1]` 的一个补全。`prefix` 用到的数字必须是这手牌的一部分，
+/// 否则（或 `prefix` 本身无法求值时）返回空列表。结果按字符串去重并排序。
+pub fn complete_expression(cards: &[i32], prefix: &Expr) -> Vec<Expr> {
+    let Some(prefix_value) = eval(prefix) else {
+        return Vec::new();
+    };
+    let mut rest: Vec<f64> = cards.iter().map(|&c| c as f64).collect();
+    for used in leaves(prefix) {
+        match rest.iter().position(|&c| c == used) {
+            Some(i) => {
+                rest.remove(i);
+            }
+            None => return Vec::new(),
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut completions = Vec::new();
+    for perm in permutations(&rest) {
+        let mut atoms = vec![(prefix_value, prefix.clone())];
+        atoms.extend(perm.iter().map(|&v| (v, Expr::Num(v))));
+        for (value, expr) in trees_for_atoms(&atoms, &OPERATORS) {
+            if (value - TARGET).abs() < EPSILON && seen.insert(expr.to_string()) {
+                completions.push(expr);
+            }
+        }
+    }
+    completions.sort_by_key(|expr| expr.to_string());
+    completions
+}
+
+/// 解在顶层运算处把牌分成的两组，例如 `(1 + 2) * (3 + 5)` 的划分是 `{1, 2}|{3, 5}`。
+///
+/// 每组内按点数从小到大排列；两组不分左右，张数少的一组在前，张数相同时按字典序，
+/// 因此 `a * b` 与 `b * a` 属于同一种划分。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Partition {
+    pub first: Vec<i32>,
+    pub second: Vec<i32>,
+}
+
+impl Partition {
+    /// 表达式顶层运算的划分；表达式不是二元运算时返回 `None`。
+    pub fn of(expr: &Expr) -> Option<Partition> {
+        let Expr::Bin(_, lhs, rhs) = expr else {
+            return None;
+        };
+        // Solver leaves are always whole card values.
+        let group = |e: &Expr| {
+            let mut values: Vec<i32> = leaves(e).into_iter().map(|v| v as i32).collect();
+            values.sort();
+            values
+        };
+        let (a, b) = (group(lhs), group(rhs));
+        let (first, second) = if (a.len(), &a) <= (b.len(), &b) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        Some(Partition { first, second })
+    }
+}
+
+impl fmt::Display for Partition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let group = |values: &[i32]| {
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        write!(f, "{{{}}}|{{{}}}", group(&self.first), group(&self.second))
+    }
+}
+
+/// 把任意张数的牌的解（即 `solve_subset` 的结果）按顶层划分分组。
+///
+/// 每组内的解按字典序排列；所有分组的并集就是 `solve_subset` 的解集。
+/// 4 张牌时只有 `{a}|{b, c, d}` 与 `{a, b}|{c, d}` 两类划分，
+/// 后者对应 `TreeShape::Balanced`。
+pub fn solutions_by_partition(cards: &[i32]) -> HashMap<Partition, Vec<String>> {
+    let mut groups: HashMap<Partition, Vec<String>> = HashMap::new();
+    for solution in solve_subset_full(cards) {
+        if let Some(partition) = Partition::of(&solution.expr) {
+            groups.entry(partition).or_default().push(solution.text);
+        }
+    }
+    for solutions in groups.values_mut() {
+        solutions.sort();
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::parse_expr;
+    use crate::solve_subset;
+
+    #[test]
+    fn test_solve_24_full_division_flag() {
+        let solutions = solve_24_full(&[3, 3, 8, 8]);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].text, "8 / (3 - (8 / 3))");
+        assert!(solutions[0].uses_division);
+        assert_eq!(solutions[0].depth, 3);
+
+        let solutions = solve_24_full(&[6, 6, 6, 6]);
+        assert!(solutions.iter().any(|s| !s.uses_division));
+        for s in &solutions {
+            assert_eq!(s.uses_division, s.text.contains('/'));
+        }
+    }
+
+    #[test]
+    fn test_solve_24_full_matches_solve_24() {
+        for cards in [[6, 2, 3, 4], [1, 5, 5, 5], [1, 1, 1, 1]] {
+            let mut texts: Vec<String> = solve_24_full(&cards)
+                .into_iter()
+                .map(|s| {
+                    assert_eq!(s.expr.to_string(), s.text);
+                    s.text
+                })
+                .collect();
+            texts.sort();
+            let mut expected = solve_24(&cards);
+            expected.sort();
+            assert_eq!(texts, expected, "{:?}", cards);
+        }
+        let trees = solve_24_canonical_trees(&[6, 2, 3, 4]);
+        let texts: Vec<String> = trees.iter().map(Expr::to_string).collect();
+        assert_eq!(texts, solve_24_canonical(&[6, 2, 3, 4]));
+    }
+
+    // Minimal stack machine used to check the RPN output independently.
+    fn eval_rpn(rpn: &str) -> f64 {
+        let mut stack: Vec<f64> = Vec::new();
+        for token in rpn.split_whitespace() {
+            if let Ok(v) = token.parse::<f64>() {
+                stack.push(v);
+                continue;
+            }
+            let b = stack.pop().unwrap();
+            let a = stack.pop().unwrap();
+            stack.push(match token {
+                "+" => a + b,
+                "-" => a - b,
+                "*" => a * b,
+                "/" => a / b,
+                _ => panic!("unexpected token {}", token),
+            });
+        }
+        assert_eq!(stack.len(), 1);
+        stack[0]
+    }
+
+    #[test]
+    fn test_solve_24_rpn_evaluates_to_target() {
+        let rpn = solve_24_rpn(&[3, 3, 8, 8]);
+        assert_eq!(rpn, vec!["8 3 8 3 / - /".to_string()]);
+        for solution in solve_24_rpn(&[6, 2, 3, 4]) {
+            assert!((eval_rpn(&solution) - 24.0).abs() < 1e-6, "{}", solution);
+        }
+    }
+
+    #[test]
+    fn test_solution_trees_iter_is_lazy_and_complete() {
+        let first = solution_trees_iter(&[6, 2, 3, 4]).next().unwrap();
+        assert!((crate::expr::eval(&first).unwrap() - 24.0).abs() < 1e-6);
+
+        let mut from_iter: Vec<String> = solution_trees_iter(&[6, 2, 3, 4])
+            .map(|expr| expr.to_string())
+            .collect();
+        from_iter.sort();
+        from_iter.dedup();
+        let mut expected = solve_24(&[6, 2, 3, 4]);
+        expected.sort();
+        assert_eq!(from_iter, expected);
+        assert_eq!(solution_trees_iter(&[1, 1, 1, 1]).count(), 0);
+    }
+
+    #[test]
+    fn test_dedup_counts_increase() {
+        let canonical = solve_24_dedup(&[6, 6, 6, 6], Dedup::Canonical).len();
+        let exact = solve_24_dedup(&[6, 6, 6, 6], Dedup::Exact).len();
+        let none = solve_24_dedup(&[6, 6, 6, 6], Dedup::None).len();
+        assert!(canonical < exact, "{} < {}", canonical, exact);
+        assert!(exact < none, "{} < {}", exact, none);
+        assert!(solve_24_canonical(&[6, 6, 6, 6]).contains(&"((6 + 6) + 6) + 6".to_string()));
+    }
+
+    #[test]
+    fn test_solve_limited() {
+        let cards = [6, 6, 6, 6];
+        let limited = solve_limited(&cards, 3);
+        assert_eq!(limited.len(), 3);
+        assert_eq!(limited.iter().collect::<HashSet<_>>().len(), 3);
+        for s in &limited {
+            assert_eq!(crate::answer::check_answer(&cards, s), Ok(true));
+        }
+        assert!(solve_limited(&cards, 0).is_empty());
+        assert_eq!(solve_limited(&[3, 3, 8, 8], 10).len(), 1);
+    }
+
+    #[test]
+    fn test_solve_limited_ordered() {
+        let cards = [6, 2, 3, 4];
+        let first = solve_limited_ordered(&cards, 1, EnumOrder::SimplestFirst);
+        assert_eq!(parse_expr(&first[0]).unwrap().depth(), 2, "{}", first[0]);
+        assert_eq!(
+            solve_limited_ordered(&cards, 5, EnumOrder::ByPermutation),
+            solve_limited(&cards, 5)
+        );
+        let by_ops = solve_limited_ordered(&[6, 6, 6, 6], 1, EnumOrder::ByOperatorCount);
+        assert_eq!(by_ops, vec!["(6 + 6) + (6 + 6)".to_string()]);
+
+        let mut expected: Vec<String> =
+            solution_trees_iter(&cards).map(|e| e.to_string()).collect();
+        expected.sort();
+        for order in [
+            EnumOrder::ByStructure,
+            EnumOrder::ByOperatorCount,
+            EnumOrder::SimplestFirst,
+        ] {
+            let mut all: Vec<String> = solution_trees_ordered(&cards, order)
+                .map(|e| e.to_string())
+                .collect();
+            all.sort();
+            assert_eq!(all, expected, "{:?}", order);
+        }
+    }
+
+    #[test]
+    fn test_solve_24_callback_matches_solve_24() {
+        let mut collected = Vec::new();
+        let flow = solve_24_callback(&[6, 2, 3, 4], |s| {
+            collected.push(s.to_string());
+            ControlFlow::Continue(())
+        });
+        assert_eq!(flow, ControlFlow::Continue(()));
+        let mut expected = solve_24(&[6, 2, 3, 4]);
+        expected.sort();
+        collected.sort();
+        assert_eq!(collected, expected);
+
+        // Breaking stops after the first solution.
+        let mut calls = 0;
+        let flow = solve_24_callback(&[6, 2, 3, 4], |_| {
+            calls += 1;
+            ControlFlow::Break(())
+        });
+        assert_eq!(flow, ControlFlow::Break(()));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_complete_expression() {
+        let cards = [6, 2, 3, 1];
+        let completions = complete_expression(&cards, &Expr::Num(6.0));
+        assert!(!completions.is_empty());
+        let texts: Vec<String> = completions.iter().map(|e| e.to_string()).collect();
+        assert!(texts.contains(&"6 * (2 + (3 - 1))".to_string()));
+        for expr in &completions {
+            assert_eq!(leaves(expr)[0], 6.0);
+            assert!(crate::answer::check_answer(&cards, &expr.to_string()).unwrap());
+        }
+
+        // A larger fixed prefix, and one that uses a card not in the hand.
+        let prefix = parse_expr("6 * 2").unwrap();
+        let completions = complete_expression(&cards, &prefix);
+        assert!(completions
+            .iter()
+            .any(|e| e.to_string() == "(6 * 2) * (3 - 1)"));
+        assert!(complete_expression(&cards, &Expr::Num(7.0)).is_empty());
+    }
+
+    #[test]
+    fn test_solve_stream_keeps_order() {
+        let hands = vec![vec![3, 3, 8, 8], vec![1, 1, 1, 1], vec![6, 2, 3, 4]];
+        let results: Vec<(Vec<i32>, Vec<String>)> =
+            solve_stream(hands.clone().into_iter()).collect();
+        let order: Vec<Vec<i32>> = results.iter().map(|(hand, _)| hand.clone()).collect();
+        assert_eq!(order, hands);
+        assert_eq!(results[0].1, vec!["8 / (3 - (8 / 3))"]);
+        assert!(results[1].1.is_empty());
+
+        // Nothing is solved until the iterator is advanced.
+        let mut solved = 0;
+        let mut stream = solve_stream((0..).map(|_| {
+            solved += 1;
+            vec![6, 2, 3, 4]
+        }));
+        stream.next();
+        drop(stream);
+        assert_eq!(solved, 1);
+    }
+
+    #[test]
+    fn test_solutions_by_partition() {
+        let cards = [1, 2, 3, 4, 5];
+        let groups = solutions_by_partition(&cards);
+        let mut grouped: Vec<String> = groups.values().flatten().cloned().collect();
+        let mut flat = solve_subset(&cards);
+        grouped.sort();
+        flat.sort();
+        assert_eq!(grouped, flat);
+        for (partition, solutions) in &groups {
+            let mut all = partition.first.clone();
+            all.extend(&partition.second);
+            all.sort();
+            assert_eq!(all, cards);
+            assert!(solutions.windows(2).all(|w| w[0] < w[1]));
+        }
+
+        let groups = solutions_by_partition(&[6, 2, 3, 4]);
+        let split = Partition {
+            first: vec![2, 6],
+            second: vec![3, 4],
+        };
+        assert_eq!(split.to_string(), "{2, 6}|{3, 4}");
+        assert!(groups[&split].contains(&"(6 * 2) + (3 * 4)".to_string()));
+    }
+}
@@ -0,0 +1,134 @@
+//! 基于随机抽牌的统计。
+
+use crate::analysis::{difficulty, DifficultyMetrics};
+use crate::count_solutions;
+use crate::draw::draw;
+use rand::Rng;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+/// 模拟 `n` 次随机抽牌，统计解的个数的分布。
+///
+/// 返回的映射中，键为一手牌的解的个数（`count_solutions` 的结果），
+/// 值为出现该解数的手数，所有值之和等于 `n`。
+/// 与穷举所有牌型不同，这里每手牌按 `draw` 的抽牌概率加权；
+/// 传入带种子的随机数生成器即可得到可复现的结果。
+pub fn simulate_distribution(n: usize, rng: &mut impl Rng) -> HashMap<usize, usize> {
+    let mut distribution = HashMap::new();
+    for _ in 0..n {
+        let hand = draw(rng);
+        *distribution.entry(count_solutions(&hand)).or_insert(0) += 1;
+    }
+    distribution
+}
+
+/// 一次运行（一局）中处理过的所有手牌的汇总，每处理一手牌调用一次 `record`。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionStats {
+    hands: usize,
+    solvable: usize,
+    total_solutions: usize,
+    hardest: Option<(Vec<i32>, DifficultyMetrics)>,
+}
+
+impl SessionStats {
+    pub fn new() -> SessionStats {
+        SessionStats::default()
+    }
+
+    /// 记录一手牌及其全部解（通常就是写入日志的那些解）。
+    pub fn record(&mut self, hand: &[i32], solutions: &[String]) {
+        self.hands += 1;
+        self.total_solutions += solutions.len();
+        if solutions.is_empty() {
+            return;
+        }
+        self.solvable += 1;
+        if let Some(metrics) = difficulty(hand) {
+            let harder = self
+                .hardest
+                .as_ref()
+                .is_none_or(|(_, best)| metrics.hardness_key() > best.hardness_key());
+            if harder {
+                self.hardest = Some((hand.to_vec(), metrics));
+            }
+        }
+    }
+
+    pub fn hands(&self) -> usize {
+        self.hands
+    }
+
+    pub fn solvable(&self) -> usize {
+        self.solvable
+    }
+
+    /// 平均每手牌的解数，无解的牌按 0 计入；还没有记录任何牌时返回 `None`。
+    pub fn average_solutions(&self) -> Option<f64> {
+        (self.hands > 0).then(|| self.total_solutions as f64 / self.hands as f64)
+    }
+
+    /// 有解的牌中按 `DifficultyMetrics::hardness_key` 最难的一手；难度相同时保留先出现的。
+    pub fn hardest(&self) -> Option<&[i32]> {
+        self.hardest.as_ref().map(|(hand, _)| hand.as_slice())
+    }
+
+    /// 多行文本形式的汇总，`write_summary` 写出的就是这段内容。
+    pub fn summary(&self) -> String {
+        let average = self
+            .average_solutions()
+            .map_or("-".to_string(), |a| format!("{:.2}", a));
+        let hardest = self
+            .hardest()
+            .map_or("-".to_string(), |h| format!("{:?}", h));
+        format!(
+            "Hands: {}\nSolvable: {}\nAverage solutions: {}\nHardest hand: {}\n",
+            self.hands, self.solvable, average, hardest
+        )
+    }
+
+    /// 把 `summary` 写入 `path`（覆盖已有内容）。
+    pub fn write_summary(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.summary())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_simulate_distribution_sums_to_n() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let distribution = simulate_distribution(50, &mut rng);
+        assert_eq!(distribution.values().sum::<usize>(), 50);
+
+        // The same seed must reproduce the same distribution.
+        let mut rng = StdRng::seed_from_u64(42);
+        assert_eq!(simulate_distribution(50, &mut rng), distribution);
+    }
+
+    #[test]
+    fn test_session_stats() {
+        let mut stats = SessionStats::new();
+        assert_eq!(stats.average_solutions(), None);
+        for hand in [[6, 2, 3, 4], [3, 3, 8, 8], [1, 1, 1, 1]] {
+            stats.record(&hand, &crate::solve_24(&hand));
+        }
+        assert_eq!(stats.hands(), 3);
+        assert_eq!(stats.solvable(), 2);
+        let expected = (count_solutions(&[6, 2, 3, 4]) + 1) as f64 / 3.0;
+        assert_eq!(stats.average_solutions(), Some(expected));
+        assert_eq!(stats.hardest(), Some(&[3, 3, 8, 8][..]));
+
+        let path = std::env::temp_dir().join(format!("session-{}.txt", std::process::id()));
+        stats.write_summary(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(text.starts_with("Hands: 3\nSolvable: 2\n"), "{}", text);
+        assert!(text.ends_with("Hardest hand: [3, 3, 8, 8]\n"), "{}", text);
+    }
+}
@@ -0,0 +1,348 @@
+//! 对所有牌型的穷举统计。
+//!
+//! 4 张 1~13 的牌不计顺序（允许重复点数）共有 C(16, 4) = 1820 种组合；
+//! `solve_24` 对每个排列都会枚举，结果只取决于这四个点数构成的多重集合，
+//! 因此穷举时每种组合只需要求解一次。
+
+use crate::analysis::{difficulty, DifficultyMetrics};
+use crate::solution::{solve_24_canonical, solve_24_canonical_trees};
+use crate::solve_with_ops;
+use crate::TreeShape;
+use crate::{count_solutions, eval_struct, is_solvable, solve_by};
+use crate::{EPSILON, OPERATORS, TARGET};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// 返回一手牌的规范形式：从小到大排序后的点数。
+///
+/// `solve_24` 的解集只取决于牌的多重集合而与给出的顺序无关，
+/// 因此规范形式相同的两手牌可以共用一次求解结果。
+pub fn canonical_hand(cards: &[i32]) -> Vec<i32> {
+    let mut hand = cards.to_vec();
+    hand.sort();
+    hand
+}
+
+/// 按字典序列出所有不计顺序的 4 张牌组合，每手牌都已从小到大排序。
+pub fn all_hands() -> Vec<Vec<i32>> {
+    let mut hands = Vec::new();
+    for a in 1..=13 {
+        for b in a..=13 {
+            for c in b..=13 {
+                for d in c..=13 {
+                    hands.push(vec![a, b, c, d]);
+                }
+            }
+        }
+    }
+    hands
+}
+
+// Canonical solutions of `cards` whose tree contains at least one division.
+fn division_solution_count(cards: &[i32]) -> usize {
+    solve_24_canonical_trees(cards)
+        .iter()
+        .filter(|expr| expr.operators().contains(&'/'))
+        .count()
+}
+
+/// 返回用到除法的解最多的 `k` 手牌，以及各自这类解的个数。
+///
+/// 解按 `solve_24_canonical` 去重，只要表达式树中出现除法就计入。
+/// 结果按个数从多到少排列，个数相同时按牌面字典序；没有除法解的牌不会出现。
+pub fn top_division_hands(k: usize) -> Vec<(Vec<i32>, usize)> {
+    let mut counted: Vec<(Vec<i32>, usize)> = all_hands()
+        .into_iter()
+        .map(|hand| {
+            let count = division_solution_count(&hand);
+            (hand, count)
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    counted.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.cmp(b)));
+    counted.truncate(k);
+    counted
+}
+
+/// 列出恰好有 `k` 个解（按 `solve_24_canonical` 去重计数）的所有牌型。
+///
+/// `k = 0` 即全部无解的牌型，`k = 1` 即解唯一的牌型；结果按牌面字典序排列。
+pub fn hands_with_solution_count(k: usize) -> Vec<Vec<i32>> {
+    all_hands()
+        .into_iter()
+        .filter(|hand| solve_24_canonical(hand).len() == k)
+        .collect()
+}
+
+/// 统计在全部 1820 种牌型中，五种括号形态各贡献了多少个解。
+///
+/// 计数口径与 `solve_24` 相同：每手牌按表达式字符串去重（不做交换律规范化）。
+/// 不同形态渲染出的括号结构不同，同一个字符串只可能来自一种形态，
+/// 因此可以逐个形态单独求解计数，各形态的计数之和等于所有牌型的 `count_solutions` 之和。
+pub fn shape_productivity() -> HashMap<TreeShape, usize> {
+    let hands = all_hands();
+    TreeShape::ALL
+        .iter()
+        .map(|&shape| {
+            let only_shape = |s: TreeShape, perm: &[f64], ops: [char; 3]| {
+                (s == shape).then(|| eval_struct(s, perm, ops)).flatten()
+            };
+            let count = hands
+                .iter()
+                .map(|hand| solve_by(hand, only_shape, |v| (v - TARGET).abs() < EPSILON).len())
+                .sum();
+            (shape, count)
+        })
+        .collect()
+}
+
+/// 对每种运算符，统计去掉它之后有多少手原本有解的牌变得无解。
+///
+/// 计数为 0 的运算符对凑 24 而言是多余的：没有它也能解出所有有解的牌。
+/// 去掉运算符后的求解用 `solve_with_ops`，与 `solution_diff` 比较两组运算符时相同。
+pub fn operator_necessity() -> HashMap<char, usize> {
+    let solvable: Vec<Vec<i32>> = all_hands().into_iter().filter(|h| is_solvable(h)).collect();
+    OPERATORS
+        .iter()
+        .map(|&removed| {
+            let ops: Vec<char> = OPERATORS
+                .iter()
+                .copied()
+                .filter(|&op| op != removed)
+                .collect();
+            let lost = solvable
+                .iter()
+                .filter(|hand| solve_with_ops(hand, &ops).is_empty())
+                .count();
+            (removed, lost)
+        })
+        .collect()
+}
+
+/// 以字符画的形式展示所有 `[i, i, j, j]` 形式的牌是否有解。
+///
+/// 共 13 行、每行 13 个字符，第 `i` 行第 `j` 列（均从 1 开始）对应 `[i, i, j, j]`，
+/// 有解为 `#`，无解为 `.`；行之间用换行分隔，末尾没有换行。
+/// 网格关于对角线对称，对角线上即四张相同的牌 `[i, i, i, i]`。
+pub fn pair_solvability_grid() -> String {
+    (1..=13)
+        .map(|i| {
+            (1..=13)
+                .map(|j| if is_solvable(&[i, i, j, j]) { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `write_survey_json_streaming` 为每手牌写出的对象。
+#[derive(Debug, Serialize)]
+struct HandEntry<'a> {
+    cards: &'a [i32],
+    count: usize,
+}
+
+/// 以 JSON 数组的形式写出全部 1820 种牌型的解数，每个元素形如
+/// `{"cards":[3,3,8,8],"count":1}`，`count` 即 `count_solutions` 的结果。
+///
+/// 每求解一手牌就立即写出对应的对象，不会先在内存中收集整个数组，
+/// 因此牌型数量增加时内存占用也不会随之增长。元素之间以 `,` 分隔，没有换行；
+/// 需要缓冲时请传入 `BufWriter`。
+pub fn write_survey_json_streaming(mut writer: impl Write) -> io::Result<()> {
+    writer.write_all(b"[")?;
+    for (i, hand) in all_hands().iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        let entry = HandEntry {
+            cards: hand,
+            count: count_solutions(hand),
+        };
+        serde_json::to_writer(&mut writer, &entry)?;
+    }
+    writer.write_all(b"]")?;
+    writer.flush()
+}
+
+/// 返回所有有解牌型中综合难度最高的一手牌及其难度指标。
+///
+/// 按 `DifficultyMetrics::hardness_key` 比较：先看解数（越少越难），
+/// 再看是否必须用除法，最后看最浅的解的深度；仍然并列时取牌面字典序最小的一手，
+/// 因此结果唯一。无解的牌型不参与比较。
+pub fn hardest_hand() -> (Vec<i32>, DifficultyMetrics) {
+    all_hands()
+        .into_iter()
+        .filter_map(|hand| difficulty(&hand).map(|metrics| (hand, metrics)))
+        .max_by(|(a, x), (b, y)| {
+            x.hardness_key()
+                .cmp(&y.hardness_key())
+                .then_with(|| b.cmp(a))
+        })
+        .expect("some hand is solvable")
+}
+
+/// 一手多重集合牌型对应的有序抽法数：4! 除以各点数重复次数的阶乘之积。
+fn ordering_count(hand: &[i32]) -> usize {
+    let mut counts = HashMap::new();
+    for &card in hand {
+        *counts.entry(card).or_insert(0usize) += 1;
+    }
+    let factorial = |n: usize| (1..=n).product::<usize>();
+    counts
+        .values()
+        .fold(factorial(hand.len()), |acc, &n| acc / factorial(n))
+}
+
+/// 随机抽一手牌时，按 `solve_24_canonical` 去重后解数的期望值。
+///
+/// 模型是 4 张牌各自独立、等概率地取 1~13（可重复），共 13^4 种有序结果。
+/// 每个多重集合按它的有序排列数加权，例如 `[1, 2, 3, 4]` 权重 24，
+/// `[6, 6, 6, 6]` 权重 1；这与 `draw`（点数互不相同）和 `draw_from_deck`
+/// （每个点数只有 4 张）的分布都略有不同。
+pub fn expected_solution_count() -> f64 {
+    let (weighted, total) = all_hands()
+        .iter()
+        .fold((0usize, 0usize), |(weighted, total), hand| {
+            let weight = ordering_count(hand);
+            (
+                weighted + weight * solve_24_canonical(hand).len(),
+                total + weight,
+            )
+        });
+    weighted as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_hands() {
+        let hands = all_hands();
+        assert_eq!(hands.len(), 1820);
+        assert!(hands.iter().all(|h| h.windows(2).all(|w| w[0] <= w[1])));
+        assert_eq!(hands[0], vec![1, 1, 1, 1]);
+        assert_eq!(hands[1819], vec![13, 13, 13, 13]);
+    }
+
+    #[test]
+    fn test_solutions_depend_only_on_multiset() {
+        let hand = [8, 3, 8, 3];
+        assert_eq!(canonical_hand(&hand), vec![3, 3, 8, 8]);
+        for cards in [[6, 2, 3, 4], [12, 1, 7, 5]] {
+            let expected = solve_24_canonical(&canonical_hand(&cards));
+            let orderings = crate::permutations(&cards.map(|c| c as f64));
+            assert_eq!(orderings.len(), 24);
+            for order in orderings {
+                let order: Vec<i32> = order.iter().map(|&v| v as i32).collect();
+                assert_eq!(solve_24_canonical(&order), expected, "{:?}", order);
+            }
+        }
+    }
+
+    #[test]
+    fn test_top_division_hands() {
+        let top = top_division_hands(5);
+        assert_eq!(top.len(), 5);
+        assert!(top.windows(2).all(|w| w[0].1 >= w[1].1));
+        for (hand, count) in &top {
+            assert_eq!(*count, division_solution_count(hand));
+            assert!(solve_24_canonical(hand).iter().any(|s| s.contains('/')));
+        }
+    }
+
+    #[test]
+    fn test_hardest_hand() {
+        let (hand, metrics) = hardest_hand();
+        assert!(crate::is_solvable(&hand));
+        assert_eq!(difficulty(&hand), Some(metrics));
+        // [3, 3, 8, 8] sets the bar: one solution, division required, depth 3.
+        assert_eq!(metrics.solutions, 1);
+        assert!(metrics.requires_division);
+        assert_eq!(metrics.min_depth, 3);
+    }
+
+    #[test]
+    fn test_hands_with_solution_count() {
+        let unsolvable: Vec<Vec<i32>> = all_hands()
+            .into_iter()
+            .filter(|hand| !crate::is_solvable(hand))
+            .collect();
+        assert_eq!(hands_with_solution_count(0), unsolvable);
+
+        let unique = hands_with_solution_count(1);
+        assert!(unique.contains(&vec![3, 3, 8, 8]));
+        assert!(unique
+            .iter()
+            .all(|hand| solve_24_canonical(hand).len() == 1));
+    }
+
+    #[test]
+    fn test_shape_productivity() {
+        let counts = shape_productivity();
+        assert_eq!(counts.len(), 5);
+        assert!(counts.values().all(|&n| n > 0), "{:?}", counts);
+        let total: usize = all_hands().iter().map(|h| crate::count_solutions(h)).sum();
+        assert_eq!(counts.values().sum::<usize>(), total);
+    }
+
+    #[test]
+    fn test_operator_necessity() {
+        let necessity = operator_necessity();
+        assert_eq!(necessity.len(), 4);
+        // [3, 3, 8, 8] needs division. No operator is redundant: even without
+        // `+` about two hundred hands become unsolvable, and `-` matters most.
+        assert!(necessity[&'/'] > 0);
+        assert!(necessity.values().all(|&n| n > 0), "{:?}", necessity);
+        assert!(necessity[&'+'] > 100, "{:?}", necessity);
+        assert_eq!(necessity.values().max(), Some(&necessity[&'-']));
+    }
+
+    #[test]
+    fn test_pair_solvability_grid() {
+        let grid = pair_solvability_grid();
+        let rows: Vec<&str> = grid.lines().collect();
+        assert_eq!(rows.len(), 13);
+        for (i, row) in rows.iter().enumerate() {
+            let row: Vec<char> = row.chars().collect();
+            assert_eq!(row.len(), 13);
+            let card = i as i32 + 1;
+            assert_eq!(row[i] == '#', is_solvable(&[card; 4]), "{}", card);
+            assert!(row.iter().all(|&c| c == '#' || c == '.'));
+        }
+        // [3, 3, 8, 8] is solvable.
+        assert_eq!(rows[2].chars().nth(7), Some('#'));
+    }
+
+    #[test]
+    fn test_write_survey_json_streaming() {
+        let mut out = Vec::new();
+        write_survey_json_streaming(&mut out).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let hands = value.as_array().unwrap();
+        assert_eq!(hands.len(), 1820);
+        assert_eq!(
+            hands[0],
+            serde_json::json!({"cards": [1, 1, 1, 1], "count": 0})
+        );
+        let hand = hands
+            .iter()
+            .find(|h| h["cards"] == serde_json::json!([3, 3, 8, 8]))
+            .unwrap();
+        assert_eq!(hand["count"], 1);
+    }
+
+    #[test]
+    fn test_expected_solution_count() {
+        assert_eq!(ordering_count(&[1, 2, 3, 4]), 24);
+        assert_eq!(ordering_count(&[3, 3, 8, 8]), 6);
+        assert_eq!(ordering_count(&[6, 6, 6, 6]), 1);
+        let total: usize = all_hands().iter().map(|h| ordering_count(h)).sum();
+        assert_eq!(total, 13usize.pow(4));
+
+        let expected = expected_solution_count();
+        // About ten canonical solutions per draw, unsolvable hands included.
+        assert!(expected > 5.0 && expected < 20.0, "{}", expected);
+    }
+}
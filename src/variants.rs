@@ -0,0 +1,872 @@
+//! 24 点的各种变体规则。
+
+use crate::card::{validate_hand, Card};
+use crate::config::{DivisionMode, SolverConfig};
+use crate::expr::Expr;
+use crate::solution::solve_24_full;
+use crate::{
+    apply_op, eval_shape, eval_struct, permutations, solve_24, solve_by, solve_subset,
+    solve_with_config, trees_for_atoms, trees_for_sequence, EPSILON, OPERATORS, TARGET,
+};
+use std::collections::HashSet;
+use std::fmt;
+
+/// 可以用一份 `SolverConfig` 描述的具名玩法，用 `solve_variant` 求解。
+///
+/// 需要额外规则的玩法（如 `solve_with_flexible_aces`、`solve_distinct_ops`）
+/// 仍是各自独立的函数，这里只收录改变目标值、运算符或计算方式的规则。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// 标准规则：`+ - * /`，目标 24，允许分数中间结果。
+    Standard,
+    /// 不允许除法，只用 `+ - *`，与 `solve_no_division` 相同。
+    NoDivision,
+    /// 每一步的中间结果都必须是整数，见 `SolverConfig::integer_intermediates`。
+    IntegerOnly,
+    /// 标准运算符，但目标值改为给定的整数，例如 `Target(36)`。
+    Target(i32),
+}
+
+impl Variant {
+    /// 这种玩法对应的求解器配置。
+    pub fn config(self) -> SolverConfig {
+        let standard = SolverConfig::default();
+        match self {
+            Variant::Standard => standard,
+            Variant::NoDivision => SolverConfig {
+                ops: vec!['+', '-', '*'],
+                ..standard
+            },
+            Variant::IntegerOnly => SolverConfig {
+                integer_intermediates: true,
+                ..standard
+            },
+            Variant::Target(target) => SolverConfig {
+                target: target as f64,
+                ..standard
+            },
+        }
+    }
+}
+
+/// 按具名玩法求解，等价于 `solve_with_config(cards, &variant.config())`。
+pub fn solve_variant(cards: &[i32], variant: Variant) -> Vec<String> {
+    solve_with_config(cards, &variant.config())
+}
+
+/// 按指定的除法方式求 24，例如 `DivisionMode::Floor` 下 `7 / 2` 得 3。
+///
+/// 解的写法不变，其中的 `/` 按 `mode` 理解；其余规则与 `solve_24` 相同。
+pub fn solve_with_div_mode(cards: &[i32], mode: DivisionMode) -> Vec<String> {
+    let config = SolverConfig {
+        division: mode,
+        ..SolverConfig::default()
+    };
+    solve_with_config(cards, &config)
+}
+
+/// `solve_with_repetition` 允许的最大表达式长度（操作数个数）。
+pub const MAX_REPETITION_LENGTH: usize = 5;
+
+/// 把 `wildcard_index` 位置的牌当作万能牌，依次尝试 1~13 的每个取值并求解。
+///
+/// 返回 `(万能牌取值, 表达式)` 列表，先按取值、再按表达式字典序排列。
+/// 内部就是对 13 种替换分别调用 `solve_24`。
+/// `wildcard_index` 越界时会 panic，与切片下标的行为一致。
+pub fn solve_with_one_wildcard(cards: &[i32], wildcard_index: usize) -> Vec<(i32, String)> {
+    let mut results = Vec::new();
+    for value in 1..=13 {
+        let mut hand = cards.to_vec();
+        hand[wildcard_index] = value;
+        let mut solutions = solve_24(&hand);
+        solutions.sort();
+        results.extend(solutions.into_iter().map(|s| (value, s)));
+    }
+    results
+}
+
+/// "每张牌可以使用任意次"的变体：用恰好 `length` 个操作数凑出 24，
+/// 每个操作数都可以是手中任意一张牌的点数（可重复选取）。
+///
+/// 这里枚举的是长度为 `length` 的所有可重复操作数序列，再对每个序列枚举所有
+/// 括号形态与运算符，而不是对四张牌做全排列。搜索空间约为
+/// `k^length * C(length-1) * 4^(length-1)`（`k` 为不同点数的个数），
+/// 因此 `length` 超过 `MAX_REPETITION_LENGTH` 时不做搜索，直接返回空列表。
+/// 结果去重并按字典序排列。
+pub fn solve_with_repetition(cards: &[i32], length: usize) -> Vec<String> {
+    if length > MAX_REPETITION_LENGTH {
+        return Vec::new();
+    }
+    let mut values: Vec<f64> = cards.iter().map(|&c| c as f64).collect();
+    values.sort_by(f64::total_cmp);
+    values.dedup();
+
+    // Build every operand sequence of the requested length, with replacement.
+    let mut sequences: Vec<Vec<f64>> = vec![Vec::new()];
+    for _ in 0..length {
+        sequences = sequences
+            .into_iter()
+            .flat_map(|seq| {
+                values.iter().map(move |&v| {
+                    let mut next = seq.clone();
+                    next.push(v);
+                    next
+                })
+            })
+            .collect();
+    }
+
+    let mut solutions = HashSet::new();
+    for seq in &sequences {
+        for (value, expr) in trees_for_sequence(seq, &OPERATORS) {
+            if (value - TARGET).abs() < EPSILON {
+                solutions.insert(expr.to_string());
+            }
+        }
+    }
+    let mut solutions: Vec<String> = solutions.into_iter().collect();
+    solutions.sort();
+    solutions
+}
+
+/// 求 24，但任何一步运算的结果都不能等于 `forbidden`（误差在 `EPSILON` 以内即视为相等）。
+///
+/// 例如禁止 12 时，`(6 * 2) + (3 * 4)` 会因为 `6 * 2` 而被排除。
+/// 检查在逐步组合时进行：算出被禁止的值后，这条路径立即放弃。
+/// 牌面本身不算运算结果，即使等于 `forbidden` 也可以使用。
+pub fn solve_forbidding_value(cards: &[i32], forbidden: f64) -> Vec<String> {
+    let apply = |a, b, op| apply_op(a, b, op).filter(|v: &f64| (v - forbidden).abs() >= EPSILON);
+    solve_by(
+        cards,
+        |shape, perm, ops| eval_shape(shape, [perm[0], perm[1], perm[2], perm[3]], ops, apply),
+        |v| (v - TARGET).abs() < EPSILON,
+    )
+}
+
+/// A 可以当 1 也可以当 14 的规则下求解，由玩家任选。
+///
+/// 对每张 A 分别尝试 1 与 14（共 2^A 的张数 种组合），每种取法调用一次 `solve_24`。
+/// 每个解前面标出各张 A 按出现顺序的取值，例如 `A=14: (14 * 2) - (2 + 2)`，
+/// 两张 A 时写作 `A=1,14: ...`；没有 A 时标签为空，即 `A=: ...`。
+/// 结果按字典序排列且不重复。
+pub fn solve_with_flexible_aces(cards: &[Card]) -> Vec<String> {
+    let aces: Vec<usize> = (0..cards.len()).filter(|&i| cards[i].is_ace()).collect();
+    let mut results = Vec::new();
+    for mask in 0..1usize << aces.len() {
+        let mut hand: Vec<i32> = cards.iter().map(Card::value).collect();
+        let mut label = Vec::new();
+        for (bit, &i) in aces.iter().enumerate() {
+            let value = if mask & (1 << bit) != 0 { 14 } else { 1 };
+            hand[i] = value;
+            label.push(value.to_string());
+        }
+        let label = label.join(",");
+        for solution in solve_24(&hand) {
+            results.push(format!("A={}: {}", label, solution));
+        }
+    }
+    results.sort();
+    results.dedup();
+    results
+}
+
+/// 固定三个运算符位中的一个后求 24。
+///
+/// `position` 为 0、1、2，指表达式中从左往右数的第几个二元运算符（即
+/// `Expr::operators` 的下标），与括号形态无关：在 `TreeShape` 的五种形态中，
+/// 三个运算符位在渲染出的字符串里总是按 `op1`、`op2`、`op3` 的顺序出现。
+/// 例如 `position = 1, op = '*'` 要求中间那个运算符是乘法。
+/// `position` 大于 2 时 panic。
+pub fn solve_with_fixed_op(cards: &[i32], position: usize, op: char) -> Vec<String> {
+    assert!(
+        position < 3,
+        "a 4-card expression has operator positions 0..3"
+    );
+    solve_by(
+        cards,
+        |shape, perm, ops| {
+            if ops[position] == op {
+                eval_struct(shape, perm, ops)
+            } else {
+                None
+            }
+        },
+        |v| (v - TARGET).abs() < EPSILON,
+    )
+}
+
+/// 四张牌外加一张可用可不用的奖励牌 `bonus` 时求 24。
+///
+/// 返回 `(是否用了奖励牌, 表达式)`：不用奖励牌的解即 `solve_24(cards)`，
+/// 用了奖励牌的解是五张牌各用一次的 `solve_subset` 结果。
+/// 先列出不用奖励牌的解，同组内按字典序排列。
+pub fn solve_with_optional(cards: &[i32], bonus: i32) -> Vec<(bool, String)> {
+    let mut with_bonus = cards.to_vec();
+    with_bonus.push(bonus);
+    let mut solutions: Vec<(bool, String)> = solve_24(cards)
+        .into_iter()
+        .map(|s| (false, s))
+        .chain(solve_subset(&with_bonus).into_iter().map(|s| (true, s)))
+        .collect();
+    solutions.sort();
+    solutions
+}
+
+/// 带符号的变体：结果的绝对值等于 `target`（误差在 `EPSILON` 以内）即算解出，
+/// 因此 `-24` 与 `24` 都可以。
+///
+/// 每个解前面标出结果的符号，例如 `+24: (5 * 5) - (1 * 1)`、
+/// `-24: (1 * 1) - (5 * 5)`。结果按字典序排列且不重复。
+/// `target` 为 0 时正负号没有区别，只搜索一次，所有解都标为 `+0`。
+pub fn solve_abs_target(cards: &[i32], target: f64) -> Vec<String> {
+    let magnitude = target.abs();
+    let signed_targets = if magnitude == 0.0 {
+        vec![0.0]
+    } else {
+        vec![magnitude, -magnitude]
+    };
+    let mut solutions: Vec<String> = signed_targets
+        .into_iter()
+        .flat_map(|signed| {
+            solve_by(cards, eval_struct, move |v| (v - signed).abs() < EPSILON)
+                .into_iter()
+                .map(move |s| format!("{:+}: {}", signed, s))
+        })
+        .collect();
+    solutions.sort();
+    solutions.dedup();
+    solutions
+}
+
+/// 判断表达式树中是否有父子两个二元运算使用了同一个运算符。
+///
+/// "相邻"指树中的父子关系，例如 `(6 + 6) + 6` 中的两个加号；
+/// `(6 + 6) * (6 + 6)` 的两个加号是兄弟而不是父子，不算相邻。
+/// 一元负号与绝对值不算一层运算，其下的二元运算与它们之上的运算视为父子。
+fn has_repeated_op(expr: &Expr) -> bool {
+    fn walk(expr: &Expr, parent: Option<char>) -> bool {
+        match expr {
+            Expr::Num(_) => false,
+            Expr::Neg(inner) | Expr::Abs(inner) => walk(inner, parent),
+            Expr::Bin(op, lhs, rhs) => {
+                parent == Some(*op) || walk(lhs, Some(*op)) || walk(rhs, Some(*op))
+            }
+        }
+    }
+    walk(expr, None)
+}
+
+/// 求 24，但排除树中父子两层使用同一运算符的解（相邻的定义见 `has_repeated_op`）。
+///
+/// 例如 `((6 + 6) + 6) + 6` 与 `(6 + 6) + (6 + 6)` 都被排除，
+/// `(6 * 6) - (6 + 6)` 则保留。解的顺序与 `solve_24` 相同。
+pub fn solve_no_repeat_ops(cards: &[i32]) -> Vec<String> {
+    solve_24_full(cards)
+        .into_iter()
+        .filter(|s| !has_repeated_op(&s.expr))
+        .map(|s| s.text)
+        .collect()
+}
+
+/// 判断表达式中是否有"乘以 1"或"除以 1"的运算。
+///
+/// 只看直接写出的牌 `1`：`x * 1`、`1 * x` 与 `x / 1` 算作无意义的运算，
+/// `1 / x` 以及结果恰好为 1 的子表达式（如 `x * (3 - 2)`）不算。
+fn has_trivial_op(expr: &Expr) -> bool {
+    let is_one = |e: &Expr| matches!(e, Expr::Num(v) if *v == 1.0);
+    match expr {
+        Expr::Num(_) => false,
+        Expr::Neg(inner) | Expr::Abs(inner) => has_trivial_op(inner),
+        Expr::Bin(op, lhs, rhs) => {
+            let trivial = match op {
+                '*' => is_one(lhs) || is_one(rhs),
+                '/' => is_one(rhs),
+                _ => false,
+            };
+            trivial || has_trivial_op(lhs) || has_trivial_op(rhs)
+        }
+    }
+}
+
+/// 求 24，但排除含有"乘以 1"或"除以 1"的解（判断标准见 `has_trivial_op`）。
+///
+/// 这类解只是把牌 `1` 消耗掉，例如 `((1 * 2) * 3) * 4`；
+/// 真正用上 1 的解如 `((1 + 2) + 3) * 4` 仍然保留。解的顺序与 `solve_24` 相同。
+pub fn solve_no_trivial_ops(cards: &[i32]) -> Vec<String> {
+    solve_24_full(cards)
+        .into_iter()
+        .filter(|s| !has_trivial_op(&s.expr))
+        .map(|s| s.text)
+        .collect()
+}
+
+// Like `trees_for_sequence`, but every negative subtree may also appear wrapped
+// in `|...|`. Wrapping a non-negative value would only duplicate it.
+fn trees_with_abs(nums: &[f64]) -> Vec<(f64, Expr)> {
+    if nums.len() == 1 {
+        return vec![(nums[0], Expr::Num(nums[0]))];
+    }
+    let mut trees = Vec::new();
+    for split in 1..nums.len() {
+        let left = trees_with_abs(&nums[..split]);
+        let right = trees_with_abs(&nums[split..]);
+        for (lv, lexpr) in &left {
+            for (rv, rexpr) in &right {
+                for op in OPERATORS {
+                    if let Some(value) = apply_op(*lv, *rv, op) {
+                        let expr = Expr::bin(op, lexpr.clone(), rexpr.clone());
+                        if value < 0.0 {
+                            trees.push((-value, Expr::Abs(Box::new(expr.clone()))));
+                        }
+                        trees.push((value, expr));
+                    }
+                }
+            }
+        }
+    }
+    trees
+}
+
+/// 允许对任意子表达式取绝对值时求 24，绝对值写作 `|x|`，例如 `|3 - 8| * ...`。
+///
+/// 只有值为负的子表达式才会被包上绝对值，对非负值取绝对值不改变结果，
+/// 只会产生重复的解。牌面都是正数，因此只有减法产生负的中间结果时这条规则才起作用。
+/// 结果去重并按字典序排列，其中也包括不用绝对值的普通解。
+///
+/// 对 1~13 的全部牌型穷举可知，绝对值只会带来新的写法，不会让无解的牌变得有解：
+/// 负的中间结果总可以通过交换某个减法的两边改写成正的。牌中有负数时则不同，
+/// 例如 `[-13, 1, 1, 1]` 按标准规则无解，取绝对值后有 `(1 + 1) * |-13 + 1|`。
+pub fn solve_with_abs(cards: &[i32]) -> Vec<String> {
+    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
+    let mut solutions: Vec<String> = permutations(&nums)
+        .iter()
+        .flat_map(|perm| trees_with_abs(perm))
+        .filter(|(value, _)| (value - TARGET).abs() < EPSILON)
+        .map(|(_, expr)| expr.to_string())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    solutions.sort();
+    solutions
+}
+
+/// "简单模式"：只保留表达式树深度不超过 `max_depth` 的解（深度的定义见 `Expr::depth`）。
+///
+/// 四张牌的解深度为 2 或 3：`max_depth = 2` 只留下 `(a op b) op (c op d)` 形态，
+/// `max_depth >= 3` 与 `solve_24` 相同，小于 2 时没有解。解的顺序与 `solve_24` 相同。
+pub fn solve_max_depth(cards: &[i32], max_depth: usize) -> Vec<String> {
+    solve_24_full(cards)
+        .into_iter()
+        .filter(|s| s.expr.depth() <= max_depth)
+        .map(|s| s.text)
+        .collect()
+}
+
+/// 只保留运算符序列是回文的解，例如 `(6 * 2) + (3 * 4)` 的 `* + *`。
+///
+/// 运算符序列即 `Expr::operators`：按中序（在表达式字符串中从左到右出现的顺序）
+/// 列出的二元运算符，与括号形态无关。四张牌的解有三个运算符，因此条件就是
+/// 第一个与第三个运算符相同。解的顺序与 `solve_24` 相同。
+pub fn solve_palindromic_ops(cards: &[i32]) -> Vec<String> {
+    solve_24_full(cards)
+        .into_iter()
+        .filter(|s| {
+            let ops = s.expr.operators();
+            ops.iter().eq(ops.iter().rev())
+        })
+        .map(|s| s.text)
+        .collect()
+}
+
+/// `solve_with_transforms` 中每张牌在参与计算前可以做的变换。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    /// 原样使用点数 `x`。
+    Identity,
+    /// 使用相反数 `-x`。
+    Negate,
+    /// 使用倒数 `1 / x`。
+    Reciprocal,
+}
+
+impl Transform {
+    /// 解的标签中使用的简写：`id`、`neg`、`rec`。
+    fn name(self) -> &'static str {
+        match self {
+            Transform::Identity => "id",
+            Transform::Negate => "neg",
+            Transform::Reciprocal => "rec",
+        }
+    }
+
+    // The transformed operand as a value and as the subtree shown in the solution.
+    fn apply(self, card: f64) -> Option<(f64, Expr)> {
+        match self {
+            Transform::Identity => Some((card, Expr::Num(card))),
+            Transform::Negate => Some((-card, Expr::Neg(Box::new(Expr::Num(card))))),
+            Transform::Reciprocal => {
+                let value = apply_op(1.0, card, '/')?;
+                Some((value, Expr::bin('/', Expr::Num(1.0), Expr::Num(card))))
+            }
+        }
+    }
+}
+
+/// 每张牌可以先做 `transforms` 中的任一变换（原样、取相反数或取倒数）再参与计算时求 24。
+///
+/// 每张牌独立选择变换，搜索空间是普通求解的 `transforms.len()^4` 倍。
+/// 每个解前面按操作数在表达式中从左到右的顺序标出所用的变换，
+/// 例如 `rec, id, id, id: ...`；倒数写作 `(1 / x)`，相反数写作 `-x`。
+/// `transforms` 为空时没有解。结果按字典序排列且不重复。
+pub fn solve_with_transforms(cards: &[i32], transforms: &[Transform]) -> Vec<String> {
+    let nums: Vec<f64> = cards.iter().map(|&x| x as f64).collect();
+    let mut choices: Vec<Vec<Transform>> = vec![Vec::new()];
+    for _ in 0..nums.len() {
+        choices = choices
+            .into_iter()
+            .flat_map(|prefix| {
+                transforms.iter().map(move |&t| {
+                    let mut next = prefix.clone();
+                    next.push(t);
+                    next
+                })
+            })
+            .collect();
+    }
+
+    let mut solutions = HashSet::new();
+    for perm in permutations(&nums) {
+        for choice in &choices {
+            let atoms: Option<Vec<(f64, Expr)>> =
+                perm.iter().zip(choice).map(|(&v, t)| t.apply(v)).collect();
+            let Some(atoms) = atoms else { continue };
+            let label: Vec<&str> = choice.iter().map(|t| t.name()).collect();
+            for (value, expr) in trees_for_atoms(&atoms, &OPERATORS) {
+                if (value - TARGET).abs() < EPSILON {
+                    solutions.insert(format!("{}: {}", label.join(", "), expr));
+                }
+            }
+        }
+    }
+    let mut solutions: Vec<String> = solutions.into_iter().collect();
+    solutions.sort();
+    solutions
+}
+
+/// `solve_distinct_ops` 找不到解时的错误：这手牌无法用互不相同的运算符凑出 24。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsatisfiable {
+    pub cards: Vec<i32>,
+    /// 这手牌在不限制运算符时是否有解，用来区分"规则太严"与"本来就无解"。
+    pub solvable: bool,
+}
+
+impl fmt::Display for Unsatisfiable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} cannot reach 24 with three different operators",
+            self.cards
+        )?;
+        if self.solvable {
+            write!(f, " (it is solvable when operators may repeat)")
+        } else {
+            write!(f, " (it has no solution at all)")
+        }
+    }
+}
+
+impl std::error::Error for Unsatisfiable {}
+
+/// 严格模式：只接受三个运算符互不相同的解。
+///
+/// 4 张牌的表达式只有三个运算符位，不可能同时用上 `+ - * /` 四种，
+/// 这里的"全部不同"指三个位置上的运算符两两不同，例如 `(6 * 4) / (3 - 2)`。
+/// 没有这样的解时返回 `Unsatisfiable`，而不是空列表，调用方可以据此给出明确提示。
+/// 成功时解的顺序与 `solve_24` 相同。
+pub fn solve_distinct_ops(cards: &[i32]) -> Result<Vec<String>, Unsatisfiable> {
+    let all = solve_24_full(cards);
+    let solvable = !all.is_empty();
+    let distinct: Vec<String> = all
+        .into_iter()
+        .filter(|s| {
+            let ops = s.expr.operators();
+            ops.iter().collect::<HashSet<_>>().len() == ops.len()
+        })
+        .map(|s| s.text)
+        .collect();
+    if distinct.is_empty() {
+        Err(Unsatisfiable {
+            cards: cards.to_vec(),
+            solvable,
+        })
+    } else {
+        Ok(distinct)
+    }
+}
+
+// Render `expr` like its `Display` output, with every leaf replaced by the
+// symbol `names` assigns to it; `None` if some leaf has no symbol.
+fn symbolic(expr: &Expr, names: &impl Fn(f64) -> Option<char>) -> Option<String> {
+    let operand = |child: &Expr| match child {
+        Expr::Bin(..) => symbolic(child, names).map(|s| format!("({})", s)),
+        _ => symbolic(child, names),
+    };
+    Some(match expr {
+        Expr::Num(v) => names(*v)?.to_string(),
+        Expr::Neg(inner) => format!("-{}", operand(inner)?),
+        Expr::Abs(inner) => format!("|{}|", symbolic(inner, names)?),
+        Expr::Bin(op, lhs, rhs) => format!("{} {} {}", operand(lhs)?, op, operand(rhs)?),
+    })
+}
+
+/// 只含两种点数的牌的"记号"求解：同点数的牌视为同一个记号。
+///
+/// 语义如下：
+/// - 这手牌必须是合法的 4 张牌（见 `validate_hand`），且恰好有两种不同的点数
+///   （如 `[3, 3, 8, 8]`、`[2, 2, 2, 7]`），否则返回 `None`；
+/// - 较小的点数记作 `A`，较大的记作 `B`，每个解中的数字都替换成对应记号，
+///   例如 `8 / (3 - (8 / 3))` 写作 `B / (A - (B / A))`；
+/// - 解集与 `solve_24` 相同，只是换了记号；结果按字典序排列且不重复，
+///   因此同点数的牌互换位置得到的解只出现一次。
+pub fn solve_two_values(cards: &[i32]) -> Option<Vec<String>> {
+    validate_hand(cards).ok()?;
+    let mut values = cards.to_vec();
+    values.sort();
+    values.dedup();
+    let [a, b] = values[..] else {
+        return None;
+    };
+    let names = |v: f64| match v {
+        v if v == a as f64 => Some('A'),
+        v if v == b as f64 => Some('B'),
+        _ => None,
+    };
+    let mut patterns: Vec<String> = solve_24_full(cards)
+        .iter()
+        .map(|solution| symbolic(&solution.expr, &names))
+        .collect::<Option<_>>()?;
+    patterns.sort();
+    patterns.dedup();
+    Some(patterns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::operators_in;
+    use crate::expr::parse_expr;
+
+    #[test]
+    fn test_wildcard_rescues_hopeless_hand() {
+        assert!(solve_24(&[1, 1, 1, 1]).is_empty());
+        let results = solve_with_one_wildcard(&[1, 1, 1, 1], 0);
+        assert!(!results.is_empty());
+        // (1 + 1 + 1) * 8 needs the wildcard to be an 8.
+        assert!(results.iter().any(|(value, _)| *value == 8));
+        for (value, _) in &results {
+            assert!(!solve_24(&[*value, 1, 1, 1]).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_solve_with_repetition() {
+        // A single 3 can only reach 24 if it is reused.
+        let solutions = solve_with_repetition(&[3], 4);
+        assert!(solutions.contains(&"((3 * 3) * 3) - 3".to_string()));
+        assert!(solve_with_repetition(&[3], 3).is_empty());
+        assert_eq!(solve_with_repetition(&[6, 4], 2), vec!["4 * 6", "6 * 4"]);
+        assert!(solve_with_repetition(&[1, 2], 0).is_empty());
+        // Too long to search: 24 = 6 * 4 has solutions, but none are returned.
+        assert!(solve_with_repetition(&[6, 4, 1], MAX_REPETITION_LENGTH + 1).is_empty());
+    }
+
+    #[test]
+    fn test_solve_forbidding_value() {
+        let cards = [6, 2, 3, 4];
+        let all = solve_24(&cards);
+        let filtered = solve_forbidding_value(&cards, 12.0);
+        assert!(all.contains(&"(6 * 2) + (3 * 4)".to_string()));
+        assert!(!filtered.contains(&"(6 * 2) + (3 * 4)".to_string()));
+        assert!(!filtered.is_empty() && filtered.len() < all.len());
+        for s in &filtered {
+            let expr = parse_expr(s).unwrap();
+            let steps = crate::rational::exact_intermediates(&expr).unwrap();
+            assert!(steps.iter().all(|v| v.to_f64() != 12.0), "{}", s);
+        }
+        // Forbidding the target itself leaves nothing.
+        assert!(solve_forbidding_value(&cards, 24.0).is_empty());
+    }
+
+    #[test]
+    fn test_solve_two_values() {
+        assert_eq!(
+            solve_two_values(&[8, 3, 3, 8]),
+            Some(vec!["B / (A - (B / A))".to_string()])
+        );
+        let patterns = solve_two_values(&[2, 2, 2, 3]).unwrap();
+        assert!(patterns.contains(&"(A * A) * (A * B)".to_string()));
+        assert!(patterns
+            .iter()
+            .all(|p| !p.chars().any(|c| c.is_ascii_digit())));
+        assert_eq!(solve_two_values(&[1, 1, 1, 1]), None);
+        assert_eq!(solve_two_values(&[6, 2, 3, 4]), None);
+        // Multi-digit cards map to symbols too; malformed hands give `None`, not a panic.
+        let patterns = solve_two_values(&[12, 12, 2, 2]).unwrap();
+        assert!(patterns.contains(&"(B + B) * (A / A)".to_string()));
+        assert_eq!(solve_two_values(&[-3, -3, 8, 8]), None);
+        assert_eq!(solve_two_values(&[3, 3, 8]), None);
+    }
+
+    #[test]
+    fn test_solve_with_flexible_aces() {
+        use crate::card::Suit;
+
+        let card = |rank, suit| Card::new(rank, suit).unwrap();
+        let hand = [
+            card(1, Suit::Spades),
+            card(2, Suit::Hearts),
+            card(2, Suit::Diamonds),
+            card(2, Suit::Clubs),
+        ];
+        // [1, 2, 2, 2] has no solution; only the high ace works.
+        assert!(solve_24(&[1, 2, 2, 2]).is_empty());
+        let solutions = solve_with_flexible_aces(&hand);
+        assert!(!solutions.is_empty());
+        assert!(solutions.iter().all(|s| s.starts_with("A=14: ")));
+        assert!(solutions.contains(&"A=14: ((14 * 2) - 2) - 2".to_string()));
+
+        let two_aces = [
+            card(1, Suit::Spades),
+            card(1, Suit::Hearts),
+            card(6, Suit::Clubs),
+            card(6, Suit::Hearts),
+        ];
+        // Both aces low: 6 * (6 - 1 - 1).
+        let solutions = solve_with_flexible_aces(&two_aces);
+        assert!(solutions.contains(&"A=1,1: 6 * ((6 - 1) - 1)".to_string()));
+    }
+
+    #[test]
+    fn test_solve_distinct_ops() {
+        let solutions = solve_distinct_ops(&[6, 2, 3, 4]).unwrap();
+        for s in &solutions {
+            let ops = operators_in(s);
+            assert_eq!(ops.iter().collect::<HashSet<_>>().len(), 3, "{}", s);
+        }
+
+        // (1 + 1 + 1) * 8 is the only solution and repeats '+'.
+        let err = solve_distinct_ops(&[1, 1, 1, 8]).unwrap_err();
+        assert!(err.solvable);
+        assert!(err.to_string().contains("three different operators"));
+        assert!(!solve_distinct_ops(&[1, 1, 1, 1]).unwrap_err().solvable);
+
+        // The sign of a negative card is not an operator.
+        let solutions = solve_distinct_ops(&[-6, -4, 3, 2]).unwrap();
+        assert!(solutions.contains(&"(-6 * -4) / (3 - 2)".to_string()));
+    }
+
+    #[test]
+    fn test_solve_with_fixed_op() {
+        let cards = [6, 2, 3, 4];
+        for position in 0..3 {
+            let solutions = solve_with_fixed_op(&cards, position, '*');
+            assert!(!solutions.is_empty());
+            for s in &solutions {
+                assert_eq!(operators_in(s)[position], '*', "{}", s);
+            }
+        }
+        let middle = solve_with_fixed_op(&cards, 1, '+');
+        assert!(middle.contains(&"(6 * 2) + (3 * 4)".to_string()));
+        assert_eq!(
+            solve_with_fixed_op(&[3, 3, 8, 8], 0, '+'),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_solve_with_optional() {
+        let solutions = solve_with_optional(&[1, 1, 1, 1], 6);
+        assert!(!solutions.is_empty());
+        assert!(solutions.iter().all(|(used, _)| *used));
+        assert!(solutions.contains(&(true, "((1 + 1) * (1 + 1)) * 6".to_string())));
+
+        let solutions = solve_with_optional(&[3, 3, 8, 8], 1);
+        assert!(solutions.contains(&(false, "8 / (3 - (8 / 3))".to_string())));
+        assert!(solutions.iter().any(|(used, _)| *used));
+        assert!(solutions.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_solve_abs_target() {
+        let solutions = solve_abs_target(&[1, 1, 5, 5], 24.0);
+        assert!(solutions.contains(&"+24: (5 * 5) - (1 * 1)".to_string()));
+        assert!(solutions.contains(&"-24: (1 * 1) - (5 * 5)".to_string()));
+        for s in &solutions {
+            let (label, expr) = s.split_once(": ").unwrap();
+            let value = crate::expr::eval(&parse_expr(expr).unwrap()).unwrap();
+            assert!(
+                (value - label.parse::<f64>().unwrap()).abs() < EPSILON,
+                "{}",
+                s
+            );
+        }
+        assert!(solve_abs_target(&[1, 1, 1, 1], 24.0).is_empty());
+
+        // Zero has one sign: no "-0" labels and no duplicates.
+        let zero = solve_abs_target(&[1, 1, 1, 1], 0.0);
+        assert!(zero.contains(&"+0: (1 + 1) - (1 + 1)".to_string()));
+        assert!(zero.iter().all(|s| s.starts_with("+0: ")), "{:?}", zero);
+        assert_eq!(solve_abs_target(&[1, 1, 1, 1], -0.0), zero);
+    }
+
+    #[test]
+    fn test_solve_variant() {
+        let cards = [6, 2, 3, 4];
+        let sorted = |mut v: Vec<String>| {
+            v.sort();
+            v
+        };
+        assert_eq!(
+            sorted(solve_variant(&cards, Variant::Standard)),
+            sorted(solve_24(&cards))
+        );
+        let no_division = solve_variant(&cards, Variant::NoDivision);
+        assert!(!no_division.is_empty());
+        assert!(no_division.iter().all(|s| !s.contains('/')));
+        assert!(solve_variant(&[3, 3, 8, 8], Variant::NoDivision).is_empty());
+        assert!(solve_variant(&[3, 3, 8, 8], Variant::IntegerOnly).is_empty());
+        assert!(solve_variant(&[1, 2, 3, 4], Variant::Target(36))
+            .contains(&"((1 + 2) * 3) * 4".to_string()));
+    }
+
+    #[test]
+    fn test_solve_with_div_mode() {
+        // (7 * 7) / (1 + 1) = 24.5 floors to 24 but rounds to 25.
+        let cards = [1, 1, 7, 7];
+        assert!(solve_with_div_mode(&cards, DivisionMode::Exact).is_empty());
+        assert!(solve_with_div_mode(&cards, DivisionMode::Round).is_empty());
+        assert_eq!(
+            solve_with_div_mode(&cards, DivisionMode::Floor),
+            vec!["(7 * 7) / (1 + 1)".to_string()]
+        );
+        assert_eq!(
+            crate::apply_op_with(7.0, 2.0, '/', DivisionMode::Round),
+            Some(4.0)
+        );
+        assert_eq!(
+            crate::apply_op_with(7.0, 0.0, '/', DivisionMode::Floor),
+            None
+        );
+    }
+
+    #[test]
+    fn test_solve_no_repeat_ops() {
+        let solutions = solve_no_repeat_ops(&[6, 6, 6, 6]);
+        assert!(!solutions.contains(&"((6 + 6) + 6) + 6".to_string()));
+        assert!(!solutions.contains(&"(6 + 6) + (6 + 6)".to_string()));
+        assert!(solutions.contains(&"(6 * 6) - (6 + 6)".to_string()));
+        // Siblings may share an operator; only parent and child may not.
+        let repeated = |s: &str| has_repeated_op(&parse_expr(s).unwrap());
+        assert!(!repeated("(1 + 3) * (2 + 4)"));
+        assert!(!repeated("8 / (3 - (8 / 3))"));
+        assert!(repeated("6 * (2 * (3 - 1))"));
+    }
+
+    #[test]
+    fn test_solve_no_trivial_ops() {
+        let cards = [1, 2, 3, 4];
+        let all = solve_24(&cards);
+        assert!(all.contains(&"((1 * 2) * 3) * 4".to_string()));
+        let solutions = solve_no_trivial_ops(&cards);
+        assert!(!solutions.contains(&"((1 * 2) * 3) * 4".to_string()));
+        assert!(!solutions.contains(&"((2 * 3) * 4) / 1".to_string()));
+        assert!(solutions.contains(&"((1 + 2) + 3) * 4".to_string()));
+        assert!(solutions.len() < all.len());
+        let trivial = |s: &str| has_trivial_op(&parse_expr(s).unwrap());
+        assert!(!trivial("(1 / 3) * 6"));
+    }
+
+    #[test]
+    fn test_solve_with_abs_rewrites_negative_intermediates() {
+        // 8 / ((8 / 3) - 3) is -24; taking the absolute value of it, or of the
+        // negative divisor, gives two solutions the standard rules cannot write.
+        let solutions = solve_with_abs(&[3, 3, 8, 8]);
+        assert_eq!(
+            solutions,
+            vec![
+                "8 / (3 - (8 / 3))".to_string(),
+                "8 / |(8 / 3) - 3|".to_string(),
+                "|8 / ((8 / 3) - 3)|".to_string(),
+            ]
+        );
+        for s in &solutions {
+            let expr = crate::expr::parse_expr_abs(s).unwrap();
+            assert_eq!(expr.to_string(), *s);
+            assert!((crate::expr::eval(&expr).unwrap() - TARGET).abs() < EPSILON);
+        }
+        assert!(solve_with_abs(&[1, 1, 1, 1]).is_empty());
+    }
+
+    #[test]
+    fn test_solve_with_abs_rescues_negative_card() {
+        // No 1..=13 hand needs abs, but a negative card can: -13 + 1 is -12.
+        let cards = [-13, 1, 1, 1];
+        assert!(solve_24(&cards).is_empty());
+        let solutions = solve_with_abs(&cards);
+        assert!(solutions.contains(&"(1 + 1) * |-13 + 1|".to_string()));
+        assert!(solutions.iter().all(|s| s.contains('|')), "{:?}", solutions);
+    }
+
+    #[test]
+    fn test_solve_max_depth() {
+        let cards = [6, 2, 3, 4];
+        let shallow = solve_max_depth(&cards, 2);
+        assert!(shallow.contains(&"(6 * 2) + (3 * 4)".to_string()));
+        for s in &shallow {
+            let expr = parse_expr(s).unwrap();
+            let Expr::Bin(_, lhs, rhs) = &expr else {
+                panic!("{}", s);
+            };
+            assert!(
+                matches!(**lhs, Expr::Bin(..)) && matches!(**rhs, Expr::Bin(..)),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(solve_max_depth(&cards, 3).len(), solve_24(&cards).len());
+        assert!(solve_max_depth(&[3, 3, 8, 8], 2).is_empty());
+        assert!(solve_max_depth(&cards, 1).is_empty());
+    }
+
+    #[test]
+    fn test_solve_palindromic_ops() {
+        let cards = [6, 2, 3, 4];
+        let palindromic = solve_palindromic_ops(&cards);
+        assert!(palindromic.contains(&"(6 * 2) + (3 * 4)".to_string()));
+        // * * -: not a palindrome.
+        assert!(solve_24(&cards).contains(&"(6 * 4) * (3 - 2)".to_string()));
+        assert!(!palindromic.contains(&"(6 * 4) * (3 - 2)".to_string()));
+        for s in &palindromic {
+            let ops = operators_in(s);
+            assert_eq!(ops[0], ops[2], "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_solve_with_transforms() {
+        let cards = [1, 2, 5, 11];
+        assert!(solve_24(&cards).is_empty());
+        let solutions =
+            solve_with_transforms(&cards, &[Transform::Identity, Transform::Reciprocal]);
+        assert!(solutions.contains(&"id, id, id, rec: (11 + 5) * (1 + (1 / 2))".to_string()));
+        assert!(solutions.iter().all(|s| s.contains("rec")));
+
+        let plain = solve_with_transforms(&[3, 3, 8, 8], &[Transform::Identity]);
+        assert_eq!(plain, vec!["id, id, id, id: 8 / (3 - (8 / 3))".to_string()]);
+        let negated =
+            solve_with_transforms(&[3, 3, 8, 8], &[Transform::Identity, Transform::Negate]);
+        assert!(negated.contains(&"neg, id, id, id: -8 / ((8 / 3) - 3)".to_string()));
+        assert!(solve_with_transforms(&[3, 3, 8, 8], &[]).is_empty());
+    }
+}
@@ -0,0 +1,52 @@
+//! 生成可打印的练习题。
+
+use crate::analysis::best_solution;
+use crate::draw::draw_solvable_hand;
+use rand::Rng;
+use std::fmt::Write;
+
+/// 生成 `n` 道有解的题目，返回 `(题目页, 答案页)` 两段纯文本。
+///
+/// 题目页只列出编号和牌面，答案页按相同编号给出每道题的 `best_solution`，
+/// 两页可以分开打印。
+pub fn generate_worksheet(n: usize, rng: &mut impl Rng) -> (String, String) {
+    let mut puzzles = String::from("24 Puzzle Worksheet\n\n");
+    let mut answers = String::from("Answer Key\n\n");
+    for i in 1..=n {
+        let hand = draw_solvable_hand(rng);
+        let cards: Vec<String> = hand.iter().map(|c| c.to_string()).collect();
+        let answer = best_solution(&hand).expect("draw_solvable_hand returns solvable hands");
+        writeln!(puzzles, "{:>3}. {}", i, cards.join("  ")).unwrap();
+        writeln!(answers, "{:>3}. {}", i, answer).unwrap();
+    }
+    (puzzles, answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::check_answer;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_worksheet_answers_match_puzzles() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let (puzzles, answers) = generate_worksheet(5, &mut rng);
+        let puzzle_lines: Vec<&str> = puzzles.lines().skip(2).collect();
+        let answer_lines: Vec<&str> = answers.lines().skip(2).collect();
+        assert_eq!(puzzle_lines.len(), 5);
+        assert_eq!(answer_lines.len(), 5);
+
+        for (p, a) in puzzle_lines.iter().zip(&answer_lines) {
+            let (p_no, cards) = p.split_once(". ").unwrap();
+            let (a_no, answer) = a.split_once(". ").unwrap();
+            assert_eq!(p_no, a_no);
+            let hand: Vec<i32> = cards
+                .split_whitespace()
+                .map(|c| c.parse().unwrap())
+                .collect();
+            assert_eq!(check_answer(&hand, answer), Ok(true));
+        }
+    }
+}
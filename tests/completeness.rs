@@ -0,0 +1,158 @@
+//! 用暴力枚举验证 `solve_24` 的完整性。
+//!
+//! 这里不复用库中的 `TreeShape` 和 `parse_expr`：直接生成四个数、四种运算符的
+//! 所有全括号表达式字符串，用本文件中独立的解析器按有理数精确求值，
+//! 再与 `solve_24` 的结果逐一比较。
+
+use std::collections::HashSet;
+use twelve_four_puzzle::solve_24;
+
+const OPS: [char; 4] = ['+', '-', '*', '/'];
+
+// Every fully-parenthesized expression over `nums` in this order, rendered the
+// way the solver does: binary operands in parentheses, the root bare.
+fn expressions(nums: &[i64]) -> Vec<String> {
+    if nums.len() == 1 {
+        return vec![nums[0].to_string()];
+    }
+    let wrap = |s: &String, n: usize| {
+        if n == 1 {
+            s.clone()
+        } else {
+            format!("({})", s)
+        }
+    };
+    let mut out = Vec::new();
+    for split in 1..nums.len() {
+        let (left, right) = nums.split_at(split);
+        for l in expressions(left) {
+            for r in expressions(right) {
+                for op in OPS {
+                    out.push(format!(
+                        "{} {} {}",
+                        wrap(&l, left.len()),
+                        op,
+                        wrap(&r, right.len())
+                    ));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn permutations(items: &[i64]) -> Vec<Vec<i64>> {
+    if items.is_empty() {
+        return vec![vec![]];
+    }
+    let mut out = Vec::new();
+    for i in 0..items.len() {
+        let mut rest = items.to_vec();
+        let head = rest.remove(i);
+        for mut tail in permutations(&rest) {
+            tail.insert(0, head);
+            out.push(tail);
+        }
+    }
+    out
+}
+
+// Exact fraction arithmetic; `None` on division by zero.
+type Frac = (i64, i64);
+
+fn combine((a, b): Frac, (c, d): Frac, op: char) -> Option<Frac> {
+    let (n, m) = match op {
+        '+' => (a * d + c * b, b * d),
+        '-' => (a * d - c * b, b * d),
+        '*' => (a * c, b * d),
+        '/' if c != 0 => (a * d, b * c),
+        _ => return None,
+    };
+    Some((n, m))
+}
+
+// Minimal parser for the fully-parenthesized strings above:
+// expr := operand (op operand)?, operand := number | '(' expr ')'.
+// The whole string is always consumed, even past a division by zero.
+fn eval(tokens: &[char], pos: &mut usize) -> Option<Frac> {
+    let lhs = operand(tokens, pos);
+    match tokens.get(*pos) {
+        Some(&op) if OPS.contains(&op) => {
+            *pos += 1;
+            let rhs = operand(tokens, pos);
+            combine(lhs?, rhs?, op)
+        }
+        _ => lhs,
+    }
+}
+
+fn operand(tokens: &[char], pos: &mut usize) -> Option<Frac> {
+    if tokens[*pos] == '(' {
+        *pos += 1;
+        let value = eval(tokens, pos);
+        assert_eq!(tokens[*pos], ')');
+        *pos += 1;
+        return value;
+    }
+    let start = *pos;
+    while tokens.get(*pos).is_some_and(|c| c.is_ascii_digit()) {
+        *pos += 1;
+    }
+    let digits: String = tokens[start..*pos].iter().collect();
+    Some((digits.parse().unwrap(), 1))
+}
+
+fn equals_24(expression: &str) -> bool {
+    let tokens: Vec<char> = expression.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut pos = 0;
+    let value = eval(&tokens, &mut pos);
+    assert_eq!(pos, tokens.len(), "trailing input in {}", expression);
+    value.is_some_and(|(n, d)| n == 24 * d)
+}
+
+fn brute_force(cards: &[i32]) -> HashSet<String> {
+    let nums: Vec<i64> = cards.iter().map(|&c| c as i64).collect();
+    permutations(&nums)
+        .iter()
+        .flat_map(|perm| expressions(perm))
+        .filter(|e| equals_24(e))
+        .collect()
+}
+
+#[test]
+fn brute_force_matches_solver() {
+    let mut hands = vec![[3, 3, 8, 8], [1, 5, 5, 5], [6, 2, 3, 4], [1, 1, 1, 1]];
+    // A spread of multisets across the whole 1..=13 range.
+    let mut index = 0;
+    for a in 1..=13 {
+        for b in a..=13 {
+            for c in b..=13 {
+                for d in c..=13 {
+                    if index % 37 == 0 {
+                        hands.push([a, b, c, d]);
+                    }
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    for hand in hands {
+        let expected = brute_force(&hand);
+        let found: HashSet<String> = solve_24(&hand).into_iter().collect();
+        assert_eq!(found, expected, "hand {:?}", hand);
+    }
+}
+
+#[test]
+fn brute_force_counts_every_tree() {
+    // Five bracketings (Catalan C3) times 4^3 operator choices per ordering.
+    assert_eq!(expressions(&[1, 2, 3, 4]).len(), 5 * 64);
+    assert_eq!(
+        expressions(&[1, 2, 3, 4])
+            .iter()
+            .collect::<HashSet<_>>()
+            .len(),
+        5 * 64
+    );
+}